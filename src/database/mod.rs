@@ -3,6 +3,13 @@ pub struct History
     history: rusqlite::Connection
 }
 
+impl Default for History
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl History
 {
     pub fn new() -> Self
@@ -95,6 +102,16 @@ impl History
 
 }
 
+#[allow(non_camel_case_types)]
+struct DB_OUT
+{
+    #[allow(dead_code)]
+    id: i32,
+    datetime: String,
+    action: String,
+
+}
+
 #[cfg(test)]
 mod tests {
     //use super::*;
@@ -105,13 +122,3 @@ mod tests {
         
     }
 }
-
-#[allow(non_camel_case_types)]
-struct DB_OUT
-{
-    #[allow(dead_code)]
-    id: i32,
-    datetime: String,
-    action: String,
-
-}
\ No newline at end of file
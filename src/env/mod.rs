@@ -14,35 +14,23 @@ pub fn get_home() -> Result<String, std::io::Error>
     Ok(home_unwrap.to_str().unwrap().trim().to_string())
 }
 
-pub fn get_exe_dir() -> Result<String, std::io::Error>
+/// The directory the running executable is in
+pub fn get_exe_dir() -> Result<std::path::PathBuf, std::io::Error>
 {
-    let exe_path = std::env::current_exe()?.display().to_string();
-    
-    #[cfg(target_os="windows")]
-    let exe = exe_path.split("\\").last().unwrap();
-
-    #[cfg(not(target_os="windows"))]
-    let exe = exe_path.split("/").last().unwrap();
+    let exe_path = std::env::current_exe()?;
+    let exe_dir = exe_path.parent();
+    if exe_dir.is_none()
+    {
+        return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Executable Directory Not Found"));
+    }
 
-    let current_path = std::env::current_exe()?.display().to_string().replace(exe, "");
-    Ok(current_path)
+    Ok(exe_dir.unwrap().to_path_buf())
 }
 
-
-
+/// Sets the working directory to the directory the running executable is in
 pub fn set_exe_dir() -> std::io::Result<()>
 {
-    let exe_path = std::env::current_exe()?.display().to_string();
-    
-    #[cfg(target_os="windows")]
-    let exe = exe_path.split("\\").last().unwrap();
-
-    #[cfg(not(target_os="windows"))]
-    let exe = exe_path.split("/").last().unwrap();
-
-    let current_path = std::env::current_exe()?.display().to_string().replace(exe, "");
-    let _ = std::env::set_current_dir(current_path);
-    Ok(())
+    std::env::set_current_dir(get_exe_dir()?)
 }
 
 #[cfg(test)]
@@ -56,4 +44,11 @@ mod tests {
     fn home() {
         println!("{}", get_home().unwrap())
     }
+
+    #[test]
+    fn exe_dir() {
+        let exe_dir = get_exe_dir().unwrap();
+        assert!(exe_dir.is_dir());
+        assert_eq!(exe_dir, std::env::current_exe().unwrap().parent().unwrap());
+    }
 }
\ No newline at end of file
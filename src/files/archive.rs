@@ -0,0 +1,721 @@
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Datelike, Local, Timelike};
+
+use super::create::create_temp;
+use super::{DownloadOptions, File, FileError};
+
+/// The kinds of archive File can extract and create
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat
+{
+    Zip,
+    Tar,
+    /// A gzipped tar, ".tar.gz" or ".tgz"
+    TarGz,
+}
+
+impl ArchiveFormat
+{
+    /// Works out the format from the file extension
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::files::ArchiveFormat;
+    ///
+    /// assert_eq!(ArchiveFormat::from_path("C:\\temp\\release.tar.gz"), Some(ArchiveFormat::TarGz));
+    /// assert_eq!(ArchiveFormat::from_path("C:\\temp\\notes.txt"), None);
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self>
+    {
+        let name = path.as_ref().file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip")
+        {
+            Some(ArchiveFormat::Zip)
+        }
+        else if name.ends_with(".tar.gz") || name.ends_with(".tgz")
+        {
+            Some(ArchiveFormat::TarGz)
+        }
+        else if name.ends_with(".tar")
+        {
+            Some(ArchiveFormat::Tar)
+        }
+        else
+        {
+            None
+        }
+    }
+}
+
+/// One file or directory in an archive, see File::list_archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry
+{
+    /// The path inside the archive, always with "/" between directories
+    pub name: String,
+    /// Size in bytes before compression
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+impl File
+{
+    /// Works out what kind of archive the file is from its first bytes, falling back to the file extension
+    pub fn archive_format(&self) -> Result<ArchiveFormat, FileError>
+    {
+        let mut start = Vec::with_capacity(512);
+        std::fs::File::open(&self.file)
+            .and_then(|file| file.take(512).read_to_end(&mut start))
+            .map_err(|error| self.io_error(error))?;
+
+        if start.starts_with(b"PK\x03\x04") || start.starts_with(b"PK\x05\x06")
+        {
+            return Ok(ArchiveFormat::Zip);
+        }
+        if start.starts_with(&[0x1F, 0x8B])
+        {
+            // Any gzipped file starts like this, it's only a tarball if there's a tar inside
+            let mut block = Vec::with_capacity(512);
+            let _ = std::fs::File::open(&self.file).and_then(|file| flate2::read::GzDecoder::new(file).take(512).read_to_end(&mut block));
+            if is_tar_header(&block)
+            {
+                return Ok(ArchiveFormat::TarGz);
+            }
+            return Err(self.io_error(io::Error::new(io::ErrorKind::InvalidData, "Not A Tar Archive")));
+        }
+        if is_tar_header(&start)
+        {
+            return Ok(ArchiveFormat::Tar);
+        }
+        ArchiveFormat::from_path(&self.file)
+            .ok_or_else(|| self.io_error(io::Error::new(io::ErrorKind::InvalidData, "Unknown Archive Format")))
+    }
+
+    /// Lists the files and directories in the archive (zip, tar or tar.gz) without extracting anything
+    /// # Examples
+    /// ```
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// if let Ok(entries) = file.set_file("C:\\temp\\release.zip").list_archive()
+    /// {
+    ///     for entry in entries
+    ///     {
+    ///         println!("{} ({} bytes)", entry.name, entry.size);
+    ///     }
+    /// }
+    /// ```
+    pub fn list_archive(&self) -> Result<Vec<ArchiveEntry>, FileError>
+    {
+        let mut entries = Vec::new();
+        self.each_entry(|entry| {
+            entries.push(ArchiveEntry { name: entry.name.clone(), size: entry.size, is_dir: entry.is_dir });
+            Ok(false)
+        })?;
+        Ok(entries)
+    }
+
+    /// Extracts everything in the archive into the directory (created if needed), replacing files that are already there.
+    /// Nothing is extracted if any entry would end up outside the directory (eg. "../../evil.exe", an absolute path,
+    /// or a link pointing out of it), FileError::UnsafeEntry is returned instead.
+    /// Returns the paths of everything extracted.
+    /// # Examples
+    /// ```no_run
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// let extracted = file.set_file("C:\\temp\\release.tar.gz").extract_to("C:\\temp\\release");
+    /// ```
+    pub fn extract_to<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<PathBuf>, FileError>
+    {
+        let dir = dir.as_ref();
+        // Checked in full first, so a bad entry at the end doesn't leave half an archive behind
+        self.each_entry(|entry| {
+            entry.check()?;
+            Ok(false)
+        })?;
+
+        std::fs::create_dir_all(dir).map_err(|error| FileError::from_io(dir, error))?;
+        let mut extracted = Vec::new();
+        self.each_entry(|entry| {
+            let path = entry.extract_in(dir)?;
+            extracted.push(path);
+            Ok(false)
+        })?;
+        Ok(extracted)
+    }
+
+    /// Extracts a single file from the archive to the destination path (or into it, if it's a directory).
+    /// The name is the path inside the archive, as given by list_archive. Links can only be extracted with extract_to.
+    /// # Examples
+    /// ```no_run
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// let result = file.set_file("C:\\temp\\release.zip").extract_entry("bin/tool.exe", "C:\\tools\\tool.exe");
+    /// ```
+    pub fn extract_entry<P: AsRef<Path>>(&self, name: &str, destination: P) -> Result<(), FileError>
+    {
+        let wanted = clean_name(name);
+        let destination = destination.as_ref();
+        let mut found = false;
+        self.each_entry(|entry| {
+            if entry.is_dir || entry.name != wanted
+            {
+                return Ok(false);
+            }
+            if entry.link.is_some()
+            {
+                return Err(FileError::Io { path: destination.to_path_buf(), source: io::Error::new(io::ErrorKind::InvalidInput, "Could Not Extract: it is a link") });
+            }
+            let destination = match entry.name.rsplit('/').next().filter(|_| destination.is_dir())
+            {
+                Some(file_name) => destination.join(file_name),
+                None => destination.to_path_buf(),
+            };
+            entry.write_to(&destination)?;
+            found = true;
+            Ok(true)
+        })?;
+
+        if !found
+        {
+            return Err(FileError::NoMatch { path: PathBuf::from(&self.file), pattern: name.to_string() });
+        }
+        Ok(())
+    }
+
+    /// Creates an archive at the path of this File out of everything in the folder, paths in the archive are relative to the folder.
+    /// Symlinks are stored as links, never followed.
+    /// The archive is written to a temporary file first and only replaces the File once it's complete.
+    /// # Examples
+    /// ```no_run
+    /// use davids_awesome_library::files::{ArchiveFormat, File};
+    ///
+    /// let mut file = File::new();
+    /// let result = file.set_file("C:\\temp\\logs.zip").create_archive("C:\\temp\\logs", ArchiveFormat::Zip);
+    /// ```
+    pub fn create_archive<P: AsRef<Path>>(&mut self, folder: P, format: ArchiveFormat) -> Result<(), FileError>
+    {
+        let folder = folder.as_ref();
+        let mut paths = Vec::new();
+        walk(folder, folder, &mut paths).map_err(|error| FileError::from_io(folder, error))?;
+
+        let (temp_path, out_file) = create_temp(Path::new(&self.file)).map_err(|error| self.io_error(error))?;
+        let written = match format
+        {
+            ArchiveFormat::Zip => write_zip(out_file, &paths),
+            ArchiveFormat::Tar => write_tar(out_file, &paths).and_then(|out_file| out_file.sync_all()),
+            ArchiveFormat::TarGz => write_tar(flate2::write::GzEncoder::new(out_file, flate2::Compression::default()), &paths)
+                .and_then(|encoder| encoder.finish())
+                .and_then(|out_file| out_file.sync_all()),
+        };
+        let result = written.and_then(|()| std::fs::rename(&temp_path, &self.file));
+        if result.is_err()
+        {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+        result.map_err(|error| self.io_error(error))
+    }
+
+    /// Downloads the archive to the path of this File (see download_with) and then extracts it into the directory
+    /// # Examples
+    /// ```no_run
+    /// use davids_awesome_library::files::{DownloadOptions, File};
+    ///
+    /// let mut file = File::new();
+    /// let extracted = file.set_file("C:\\temp\\release.zip")
+    ///     .download_and_extract("https://example.com/release.zip", DownloadOptions::new().retries(3, std::time::Duration::from_secs(1)), "C:\\temp\\release");
+    /// ```
+    pub fn download_and_extract<P: AsRef<Path>>(&mut self, uri: &str, options: &DownloadOptions, dir: P) -> Result<Vec<PathBuf>, FileError>
+    {
+        self.download_with(uri, options)?;
+        self.extract_to(dir)
+    }
+
+    /// Calls "visit" for every entry in the archive until it returns true
+    fn each_entry<F>(&self, mut visit: F) -> Result<(), FileError>
+    where
+        F: FnMut(&mut Entry<'_, '_>) -> Result<bool, FileError>,
+    {
+        let format = self.archive_format()?;
+        let archive_path = Path::new(&self.file);
+        let file = std::fs::File::open(archive_path).map_err(|error| self.io_error(error))?;
+        match format
+        {
+            ArchiveFormat::Zip => {
+                let mut archive = zip::ZipArchive::new(io::BufReader::new(file)).map_err(|error| self.io_error(error.into()))?;
+                for index in 0..archive.len()
+                {
+                    let mut zip_file = archive.by_index(index).map_err(|error| self.io_error(error.into()))?;
+                    let raw_name = zip_file.name().map_err(|error| self.io_error(error.into()))?.replace('\\', "/");
+                    if clean_name(&raw_name).is_empty()
+                    {
+                        continue;
+                    }
+                    let mut entry = Entry {
+                        archive: archive_path,
+                        name: clean_name(&raw_name),
+                        raw_name,
+                        size: zip_file.size(),
+                        is_dir: zip_file.is_dir(),
+                        link: None,
+                        mode: zip_file.unix_mode(),
+                        contents: Contents::Zip(&mut zip_file),
+                    };
+                    if entry.is_zip_symlink()
+                    {
+                        let mut target = String::new();
+                        entry.contents.read_to_string(&mut target).map_err(|error| self.io_error(error))?;
+                        entry.link = Some(PathBuf::from(target));
+                    }
+                    if visit(&mut entry)?
+                    {
+                        break;
+                    }
+                }
+            }
+            ArchiveFormat::Tar | ArchiveFormat::TarGz => {
+                let reader: Box<dyn Read> = if format == ArchiveFormat::TarGz { Box::new(flate2::read::GzDecoder::new(file)) } else { Box::new(file) };
+                let mut archive = tar::Archive::new(reader);
+                for tar_entry in archive.entries().map_err(|error| self.io_error(error))?
+                {
+                    let mut tar_entry = tar_entry.map_err(|error| self.io_error(error))?;
+                    let raw_name = tar_entry.path().map_err(|error| self.io_error(error))?.to_string_lossy().to_string();
+                    // Many tars start with "./" for the directory they were made from
+                    if clean_name(&raw_name).is_empty()
+                    {
+                        continue;
+                    }
+                    let entry_type = tar_entry.header().entry_type();
+                    let link = match entry_type.is_symlink() || entry_type.is_hard_link()
+                    {
+                        true => tar_entry.link_name().map_err(|error| self.io_error(error))?.map(|link| link.to_path_buf()),
+                        false => None,
+                    };
+                    let mut entry = Entry {
+                        archive: archive_path,
+                        name: clean_name(&raw_name),
+                        raw_name,
+                        size: tar_entry.size(),
+                        is_dir: entry_type.is_dir(),
+                        link,
+                        mode: None,
+                        contents: Contents::Tar(&mut tar_entry, entry_type.is_hard_link()),
+                    };
+                    if visit(&mut entry)?
+                    {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An entry being read out of a zip or tar, handed to the callback of File::each_entry
+struct Entry<'a, 'b>
+{
+    archive: &'a Path,
+    /// The name as stored, which may not be safe to extract
+    raw_name: String,
+    /// The name without "./" in front or "/" at the end
+    name: String,
+    size: u64,
+    is_dir: bool,
+    /// Where a symlink (or tar hard link) points
+    link: Option<PathBuf>,
+    mode: Option<u32>,
+    contents: Contents<'a, 'b>,
+}
+
+enum Contents<'a, 'b>
+{
+    Zip(&'a mut zip::read::ZipFile<'b, io::BufReader<std::fs::File>>),
+    /// The flag is set for hard links
+    Tar(&'a mut tar::Entry<'b, Box<dyn Read>>, bool),
+}
+
+impl Read for Contents<'_, '_>
+{
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        match self
+        {
+            Contents::Zip(zip_file) => zip_file.read(buffer),
+            Contents::Tar(tar_entry, _) => tar_entry.read(buffer),
+        }
+    }
+}
+
+impl Entry<'_, '_>
+{
+    /// The path relative to the extraction directory, an error if it (or where it links to) would be outside it
+    fn check(&self) -> Result<PathBuf, FileError>
+    {
+        let unsafe_entry = || self.unsafe_entry();
+        let relative = safe_path(&self.raw_name).ok_or_else(unsafe_entry)?;
+
+        if let Some(link) = &self.link
+        {
+            // Hard links in tars are relative to the archive root, symlinks to the directory they are in
+            let is_hard_link = matches!(self.contents, Contents::Tar(_, true));
+            let base = if is_hard_link { Path::new("") } else { relative.parent().unwrap_or(Path::new("")) };
+            safe_path(&base.join(link).to_string_lossy()).ok_or_else(unsafe_entry)?;
+        }
+        Ok(relative)
+    }
+
+    fn unsafe_entry(&self) -> FileError
+    {
+        FileError::UnsafeEntry { path: self.archive.to_path_buf(), entry: self.raw_name.clone() }
+    }
+
+    fn is_zip_symlink(&self) -> bool
+    {
+        // The file type bits of the unix mode
+        matches!(self.contents, Contents::Zip(_)) && self.mode.is_some_and(|mode| mode & 0o170000 == 0o120000)
+    }
+
+    /// Extracts the entry under "dir", returning where it ended up
+    fn extract_in(&mut self, dir: &Path) -> Result<PathBuf, FileError>
+    {
+        let relative = self.check()?;
+        let path = dir.join(&relative);
+        if let Contents::Tar(tar_entry, _) = &mut self.contents
+        {
+            // tar handles links, permissions and times itself, and checks the path again
+            tar_entry.unpack_in(dir).map_err(|error| FileError::from_io(&path, error))?;
+            return Ok(path);
+        }
+
+        // Links from earlier entries exist by now, so checking the names alone isn't enough. Every directory is resolved
+        // as it's made and has to still be inside the extraction directory, the same as tar's unpack_in does.
+        let root = std::fs::canonicalize(dir).map_err(|error| FileError::from_io(dir, error))?;
+        let dirs = if self.is_dir { relative.as_path() } else { relative.parent().unwrap_or(Path::new("")) };
+        let mut parent = root.clone();
+        for part in dirs.components()
+        {
+            let next = parent.join(part);
+            if std::fs::symlink_metadata(&next).is_err()
+            {
+                std::fs::create_dir(&next).map_err(|error| FileError::from_io(&path, error))?;
+            }
+            // A link that can't be resolved is as suspicious as one that leads outside
+            parent = std::fs::canonicalize(&next).map_err(|_| self.unsafe_entry())?;
+            if !parent.starts_with(&root)
+            {
+                return Err(self.unsafe_entry());
+            }
+        }
+        if self.is_dir
+        {
+            return Ok(path);
+        }
+
+        // Never write through a link an earlier entry left at the same name
+        let target = parent.join(relative.file_name().unwrap_or_default());
+        if std::fs::symlink_metadata(&target).is_ok_and(|metadata| metadata.file_type().is_symlink())
+        {
+            std::fs::remove_file(&target).map_err(|error| FileError::from_io(&path, error))?;
+        }
+
+        #[cfg(unix)]
+        if let Some(link) = &self.link
+        {
+            std::os::unix::fs::symlink(link, &target).map_err(|error| FileError::from_io(&path, error))?;
+            return Ok(path);
+        }
+
+        self.write_to(&target)?;
+        #[cfg(unix)]
+        if let Some(mode) = self.mode
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&target, std::fs::Permissions::from_mode(mode & 0o777)).map_err(|error| FileError::from_io(&path, error))?;
+        }
+        Ok(path)
+    }
+
+    fn write_to(&mut self, path: &Path) -> Result<(), FileError>
+    {
+        let mut out_file = std::fs::File::create(path).map_err(|error| FileError::from_io(path, error))?;
+        io::copy(&mut self.contents, &mut out_file).map_err(|error| FileError::from_io(path, error))?;
+        Ok(())
+    }
+}
+
+/// The name without "./" in front or "/" at the end, so "./bin/" and "bin" match
+fn clean_name(name: &str) -> String
+{
+    let name = name.replace('\\', "/");
+    let mut name = name.as_str();
+    while let Some(rest) = name.strip_prefix("./")
+    {
+        name = rest;
+    }
+    name.trim_end_matches('/').to_string()
+}
+
+/// The name as a relative path, None if it's absolute or ".." takes it above where it starts
+fn safe_path(name: &str) -> Option<PathBuf>
+{
+    let mut path = PathBuf::new();
+    for component in Path::new(&name.replace('\\', "/")).components()
+    {
+        match component
+        {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !path.pop()
+                {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    // Drive letters are only a prefix on Windows
+    if path.as_os_str().is_empty() || path.to_string_lossy().contains(':')
+    {
+        return None;
+    }
+    Some(path)
+}
+
+/// Whether the block is the header of a tar entry, by the "ustar" magic or (for old tars without it) the header checksum
+fn is_tar_header(block: &[u8]) -> bool
+{
+    if block.len() < 512
+    {
+        return false;
+    }
+    if block.get(257..262) == Some(b"ustar")
+    {
+        return true;
+    }
+
+    // The checksum is worked out with its own field counted as spaces
+    let stored = std::str::from_utf8(&block[148..156]).ok()
+        .and_then(|field| u32::from_str_radix(field.trim_matches(['\0', ' ']), 8).ok());
+    let sum: u32 = block[..512].iter().enumerate().map(|(index, byte)| if (148..156).contains(&index) { b' ' as u32 } else { *byte as u32 }).sum();
+    stored == Some(sum)
+}
+
+/// Everything under "dir", parents before their contents and in name order, with their names relative to "base".
+/// Symlinks are listed but never followed, so a link to a directory isn't walked into.
+fn walk(dir: &Path, base: &Path, paths: &mut Vec<(PathBuf, String)>) -> io::Result<()>
+{
+    let mut children: Vec<PathBuf> = std::fs::read_dir(dir)?.map(|child| child.map(|child| child.path())).collect::<io::Result<_>>()?;
+    children.sort();
+    for child in children
+    {
+        let relative = child.strip_prefix(base).map_err(io::Error::other)?;
+        let name = relative.components().map(|part| part.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+        let is_dir = std::fs::symlink_metadata(&child)?.is_dir();
+        paths.push((child.clone(), name));
+        if is_dir
+        {
+            walk(&child, base, paths)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_zip(out_file: std::fs::File, paths: &[(PathBuf, String)]) -> io::Result<()>
+{
+    let mut writer = zip::ZipWriter::new(out_file);
+    for (path, name) in paths
+    {
+        let metadata = std::fs::symlink_metadata(path)?;
+        let mut options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        if let Some(modified) = metadata.modified().ok().and_then(zip_time)
+        {
+            options = options.last_modified_time(modified);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            options = options.unix_permissions(metadata.permissions().mode() & 0o777);
+        }
+
+        if metadata.is_symlink()
+        {
+            writer.add_symlink(name.as_str(), std::fs::read_link(path)?.to_string_lossy(), options)?;
+        }
+        else if metadata.is_dir()
+        {
+            writer.add_directory(name.as_str(), options)?;
+        }
+        else
+        {
+            writer.start_file(name.as_str(), options)?;
+            io::copy(&mut std::fs::File::open(path)?, &mut writer)?;
+        }
+    }
+    writer.finish()?.sync_all()
+}
+
+fn write_tar<W: Write>(out: W, paths: &[(PathBuf, String)]) -> io::Result<W>
+{
+    let mut builder = tar::Builder::new(out);
+    // Links are stored as links
+    builder.follow_symlinks(false);
+    for (path, name) in paths
+    {
+        if std::fs::symlink_metadata(path)?.is_dir()
+        {
+            builder.append_dir(name, path)?;
+        }
+        else
+        {
+            builder.append_path_with_name(path, name)?;
+        }
+    }
+    builder.into_inner()
+}
+
+/// Zip only stores local times between 1980 and 2107
+fn zip_time(time: std::time::SystemTime) -> Option<zip::DateTime>
+{
+    let time: DateTime<Local> = time.into();
+    zip::DateTime::from_date_and_time(
+        u16::try_from(time.year()).ok()?,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    ).ok()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::{serve, TestDir};
+
+    #[test]
+    fn archives() {
+
+        let dir = TestDir::new("archives");
+        let source = dir.join("source");
+        std::fs::create_dir_all(source.join("bin")).unwrap();
+        std::fs::create_dir_all(source.join("empty")).unwrap();
+        std::fs::write(source.join("readme.txt"), "read me").unwrap();
+        std::fs::write(source.join("bin").join("tool.sh"), "#!/bin/sh\necho hi\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(source.join("bin").join("tool.sh"), std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let mut file = File::new();
+        for (name, format) in [("release.zip", ArchiveFormat::Zip), ("release.tar", ArchiveFormat::Tar), ("release.tar.gz", ArchiveFormat::TarGz)]
+        {
+            let archive = dir.join(name);
+            file.set_file(archive.to_str().unwrap());
+            file.create_archive(&source, format).unwrap();
+            assert_eq!(file.archive_format().unwrap(), format);
+
+            let entries = file.list_archive().unwrap();
+            let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+            assert_eq!(names, vec!["bin", "bin/tool.sh", "empty", "readme.txt"], "{}", name);
+            assert!(entries[0].is_dir);
+            assert_eq!(entries[3].size, 7);
+
+            let out = dir.join(format!("{}_out", name));
+            let extracted = file.extract_to(&out).unwrap();
+            assert_eq!(extracted.len(), 4);
+            assert_eq!(std::fs::read_to_string(out.join("bin").join("tool.sh")).unwrap(), "#!/bin/sh\necho hi\n");
+            assert!(out.join("empty").is_dir());
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                assert_eq!(std::fs::metadata(out.join("bin").join("tool.sh")).unwrap().permissions().mode() & 0o777, 0o755);
+            }
+
+            std::fs::create_dir_all(dir.join("single")).unwrap();
+            file.extract_entry("./readme.txt", dir.join("single")).unwrap();
+            assert_eq!(std::fs::read_to_string(dir.join("single").join("readme.txt")).unwrap(), "read me");
+            assert!(matches!(file.extract_entry("missing.txt", dir.join("single")), Err(FileError::NoMatch { .. })));
+        }
+
+        // Entries that would land outside the directory stop the whole extraction
+        let evil = dir.join("evil.zip");
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(&evil).unwrap());
+        writer.start_file("fine.txt", zip::write::SimpleFileOptions::default()).unwrap();
+        writer.start_file("../escaped.txt", zip::write::SimpleFileOptions::default()).unwrap();
+        writer.finish().unwrap();
+        file.set_file(evil.to_str().unwrap());
+        assert_eq!(file.list_archive().unwrap().len(), 2);
+        match file.extract_to(dir.join("evil_out")).unwrap_err()
+        {
+            FileError::UnsafeEntry { entry, .. } => assert_eq!(entry, "../escaped.txt"),
+            error => panic!("unexpected error {:?}", error),
+        }
+        assert!(!dir.join("evil_out").exists() && !dir.join("escaped.txt").exists());
+
+        let evil = dir.join("evil.tar");
+        let mut builder = tar::Builder::new(std::fs::File::create(&evil).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        builder.append_link(&mut header, "link", "../../outside").unwrap();
+        builder.finish().unwrap();
+        file.set_file(evil.to_str().unwrap());
+        assert!(matches!(file.extract_to(dir.join("evil_out")), Err(FileError::UnsafeEntry { .. })));
+
+        // Links that are each fine on their own but lead outside when followed one after the other
+        #[cfg(unix)]
+        {
+            let evil = dir.join("chained.zip");
+            let mut writer = zip::ZipWriter::new(std::fs::File::create(&evil).unwrap());
+            let options = zip::write::SimpleFileOptions::default();
+            writer.add_directory("z", options).unwrap();
+            writer.add_symlink("a/b/c/m", "../../../z", options).unwrap();
+            writer.add_symlink("a/b/c/l", "m/../../..", options).unwrap();
+            writer.start_file("a/b/c/l/evil.txt", options).unwrap();
+            std::io::Write::write_all(&mut writer, b"evil").unwrap();
+            writer.finish().unwrap();
+
+            file.set_file(evil.to_str().unwrap());
+            let out = dir.join("work").join("out");
+            assert!(matches!(file.extract_to(&out), Err(FileError::UnsafeEntry { .. })));
+            assert!(!dir.join("evil.txt").exists());
+            assert!(!dir.join("work").join("evil.txt").exists());
+        }
+
+        // Links are archived as links, so a loop doesn't go on forever and nothing outside the folder is pulled in
+        #[cfg(unix)]
+        {
+            let linked = dir.join("linked");
+            std::fs::create_dir_all(linked.join("folder")).unwrap();
+            std::os::unix::fs::symlink("..", linked.join("folder").join("loop")).unwrap();
+            std::os::unix::fs::symlink(&source, linked.join("elsewhere")).unwrap();
+            for (name, format) in [("linked.zip", ArchiveFormat::Zip), ("linked.tar.gz", ArchiveFormat::TarGz)]
+            {
+                file.set_file(dir.join(name).to_str().unwrap());
+                file.create_archive(&linked, format).unwrap();
+                let names: Vec<String> = file.list_archive().unwrap().into_iter().map(|entry| entry.name).collect();
+                assert_eq!(names, vec!["elsewhere", "folder", "folder/loop"], "{}", name);
+
+                // A link can't be written out as a file
+                assert!(matches!(file.extract_entry("folder/loop", dir.join("single")), Err(FileError::Io { .. })));
+                assert!(!dir.join("single").join("loop").exists());
+            }
+        }
+
+        // A gzipped file is only a tarball if there's a tar inside
+        let plain = dir.join("notes.tar.gz");
+        let mut encoder = flate2::write::GzEncoder::new(std::fs::File::create(&plain).unwrap(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &[b'x'; 1000]).unwrap();
+        encoder.finish().unwrap();
+        file.set_file(plain.to_str().unwrap());
+        assert!(file.archive_format().is_err());
+        assert!(file.list_archive().is_err());
+
+        // Straight from a download
+        let url = serve(std::fs::read(dir.join("release.zip")).unwrap(), 1);
+        file.set_file(dir.join("downloaded.zip").to_str().unwrap());
+        let extracted = file.download_and_extract(&format!("{}/release.zip", url), &DownloadOptions::new(), dir.join("downloaded")).unwrap();
+        assert_eq!(extracted.len(), 4);
+        assert_eq!(std::fs::read_to_string(dir.join("downloaded").join("readme.txt")).unwrap(), "read me");
+    }
+}
@@ -0,0 +1,391 @@
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::create::create_temp;
+use super::hash::{hash_path, HashAlgorithm};
+use super::{File, FileError};
+
+type ProgressCallback = Arc<dyn Fn(&CopyProgress) + Send + Sync>;
+
+/// What File::copy_to and File::move_to do when the destination already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overwrite
+{
+    /// Fail with an AlreadyExists error
+    #[default]
+    Never,
+    Always,
+    /// Only replace the destination if the file was modified after it, otherwise it's skipped
+    IfNewer,
+}
+
+/// How a copy is checked before it replaces the destination
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Verify
+{
+    #[default]
+    None,
+    /// The copy has the same number of bytes
+    Size,
+    /// The copy has the same checksum, this reads both files again
+    Hash(HashAlgorithm),
+}
+
+/// How far along a copy is, handed to the callback set with CopyOptions::on_progress
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CopyProgress
+{
+    pub copied: u64,
+    pub total: u64,
+    pub bytes_per_second: f64,
+}
+
+/// Settings for File::copy_to and File::move_to.
+/// By default an existing destination is never overwritten, and the modified time and permissions are kept.
+#[derive(Clone)]
+pub struct CopyOptions
+{
+    overwrite: Overwrite,
+    preserve: bool,
+    verify: Verify,
+    progress: Option<ProgressCallback>,
+}
+
+impl Default for CopyOptions
+{
+    fn default() -> Self {
+        CopyOptions { overwrite: Overwrite::Never, preserve: true, verify: Verify::None, progress: None }
+    }
+}
+
+impl std::fmt::Debug for CopyOptions
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CopyOptions")
+            .field("overwrite", &self.overwrite)
+            .field("preserve", &self.preserve)
+            .field("verify", &self.verify)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+impl CopyOptions
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn overwrite(&mut self, overwrite: Overwrite) -> &mut Self
+    {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Whether the copy gets the accessed/modified times and permissions of the original
+    pub fn preserve(&mut self, preserve: bool) -> &mut Self
+    {
+        self.preserve = preserve;
+        self
+    }
+
+    pub fn verify(&mut self, verify: Verify) -> &mut Self
+    {
+        self.verify = verify;
+        self
+    }
+
+    /// Called after every chunk that is copied. A move that is only a rename reports once, with everything copied.
+    pub fn on_progress<F: Fn(&CopyProgress) + Send + Sync + 'static>(&mut self, callback: F) -> &mut Self
+    {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    fn report(&self, copied: u64, total: u64, started: Instant)
+    {
+        if let Some(progress) = &self.progress
+        {
+            let elapsed = started.elapsed().as_secs_f64();
+            progress(&CopyProgress { copied, total, bytes_per_second: if elapsed > 0.0 { copied as f64 / elapsed } else { 0.0 } });
+        }
+    }
+}
+
+/// What File::copy_to or File::move_to did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyAction
+{
+    Copied,
+    /// Moved by renaming, nothing had to be copied
+    Renamed,
+    /// Copied to another filesystem and the original removed
+    CopiedAndRemoved,
+    /// The destination was newer (see Overwrite::IfNewer) so nothing was done
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyReport
+{
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub size: u64,
+    pub action: CopyAction,
+}
+
+impl std::fmt::Display for CopyReport
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = match self.action
+        {
+            CopyAction::Copied => "Copied",
+            CopyAction::Renamed | CopyAction::CopiedAndRemoved => "Moved",
+            CopyAction::Skipped => "Skipped",
+        };
+        write!(f, "{} {} to {} ({} bytes)", action, self.from.display(), self.to.display(), self.size)
+    }
+}
+
+impl File
+{
+    /// Copies the file, if the destination is a directory the copy goes inside it with the same name.
+    /// The copy is written next to the destination first and only replaces it once it's complete (and verified).
+    /// # Examples
+    /// ```no_run
+    /// use davids_awesome_library::files::{CopyOptions, File, Overwrite, Verify, HashAlgorithm};
+    ///
+    /// let mut file = File::new();
+    /// let report = file.set_file("C:\\temp\\installer.exe").copy_to("D:\\backup", CopyOptions::new()
+    ///     .overwrite(Overwrite::IfNewer)
+    ///     .verify(Verify::Hash(HashAlgorithm::Sha256))
+    ///     .on_progress(|progress| println!("{} of {} bytes", progress.copied, progress.total)));
+    /// ```
+    pub fn copy_to<P: AsRef<Path>>(&self, destination: P, options: &CopyOptions) -> Result<CopyReport, FileError>
+    {
+        let (from, to, size) = self.prepare_copy(destination.as_ref(), options)?;
+        if let Some(report) = skip(&from, &to, size, options)?
+        {
+            return Ok(report);
+        }
+
+        copy_verified(&from, &to, size, options)?;
+        Ok(CopyReport { from, to, size, action: CopyAction::Copied })
+    }
+
+    /// Moves the file by renaming it, falling back to copying it and removing the original when it has to go to another filesystem
+    /// (eg. another drive). Any other reason the rename fails (eg. no permission) is returned as an error.
+    /// The File points at the new location afterwards.
+    /// # Examples
+    /// ```no_run
+    /// use davids_awesome_library::files::{CopyOptions, File, Overwrite};
+    ///
+    /// let mut file = File::new();
+    /// let report = file.set_file("C:\\temp\\installer.exe").move_to("D:\\installers\\installer.exe", CopyOptions::new().overwrite(Overwrite::Always));
+    /// ```
+    pub fn move_to<P: AsRef<Path>>(&mut self, destination: P, options: &CopyOptions) -> Result<CopyReport, FileError>
+    {
+        let (from, to, size) = self.prepare_copy(destination.as_ref(), options)?;
+        if let Some(report) = skip(&from, &to, size, options)?
+        {
+            return Ok(report);
+        }
+
+        let action = match std::fs::rename(&from, &to)
+        {
+            Ok(()) => {
+                options.report(size, size, Instant::now());
+                CopyAction::Renamed
+            }
+            Err(error) if error.kind() == io::ErrorKind::CrossesDevices => {
+                copy_verified(&from, &to, size, options)?;
+                std::fs::remove_file(&from).map_err(|error| FileError::from_io(&from, error))?;
+                CopyAction::CopiedAndRemoved
+            }
+            Err(error) => return Err(FileError::from_io(&from, error)),
+        };
+
+        self.file = to.to_string_lossy().to_string();
+        Ok(CopyReport { from, to, size, action })
+    }
+
+    /// Works out where the file goes, and checks it isn't being copied onto itself or over something it shouldn't
+    fn prepare_copy(&self, destination: &Path, options: &CopyOptions) -> Result<(PathBuf, PathBuf, u64), FileError>
+    {
+        let from = PathBuf::from(&self.file);
+        let metadata = std::fs::metadata(&from).map_err(|error| self.io_error(error))?;
+        if metadata.is_dir()
+        {
+            return Err(self.io_error(io::Error::new(io::ErrorKind::InvalidInput, "Could Not Copy: it is a directory")));
+        }
+
+        let to = if destination.is_dir() { destination.join(from.file_name().unwrap_or_default()) } else { destination.to_path_buf() };
+        if to.exists()
+        {
+            if std::fs::canonicalize(&from).ok() == std::fs::canonicalize(&to).ok()
+            {
+                return Err(FileError::Io { path: to, source: io::Error::new(io::ErrorKind::InvalidInput, "Source And Destination Are The Same File") });
+            }
+            if options.overwrite == Overwrite::Never
+            {
+                return Err(FileError::Io { path: to, source: io::Error::new(io::ErrorKind::AlreadyExists, "Destination Already Exists") });
+            }
+        }
+        Ok((from, to, metadata.len()))
+    }
+}
+
+/// A report saying the copy was skipped, if the destination is at least as new as the file and only newer files should replace it
+fn skip(from: &Path, to: &Path, size: u64, options: &CopyOptions) -> Result<Option<CopyReport>, FileError>
+{
+    if options.overwrite != Overwrite::IfNewer || !to.exists()
+    {
+        return Ok(None);
+    }
+
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).map_err(|error| FileError::from_io(path, error));
+    if modified(from)? > modified(to)?
+    {
+        return Ok(None);
+    }
+    Ok(Some(CopyReport { from: from.to_path_buf(), to: to.to_path_buf(), size, action: CopyAction::Skipped }))
+}
+
+/// Copies into a temporary file next to the destination, checks it, then renames it over the destination
+fn copy_verified(from: &Path, to: &Path, size: u64, options: &CopyOptions) -> Result<(), FileError>
+{
+    let (temp_path, out_file) = create_temp(to).map_err(|error| FileError::from_io(to, error))?;
+    let result = copy_contents(from, out_file, size, options)
+        .map_err(|error| FileError::from_io(to, error))
+        .and_then(|()| verify(from, &temp_path, to, options.verify))
+        .and_then(|()| std::fs::rename(&temp_path, to).map_err(|error| FileError::from_io(to, error)));
+    if result.is_err()
+    {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn copy_contents(from: &Path, mut out_file: std::fs::File, size: u64, options: &CopyOptions) -> io::Result<()>
+{
+    let mut in_file = std::fs::File::open(from)?;
+
+    let started = Instant::now();
+    let mut copied = 0;
+    let mut buffer = vec![0u8; 1024 * 1024];
+    options.report(copied, size, started);
+    loop
+    {
+        let read = in_file.read(&mut buffer)?;
+        if read == 0
+        {
+            break;
+        }
+        out_file.write_all(&buffer[..read])?;
+        copied += read as u64;
+        options.report(copied, size, started);
+    }
+
+    if options.preserve
+    {
+        let metadata = in_file.metadata()?;
+        let mut times = std::fs::FileTimes::new();
+        if let Ok(accessed) = metadata.accessed()
+        {
+            times = times.set_accessed(accessed);
+        }
+        if let Ok(modified) = metadata.modified()
+        {
+            times = times.set_modified(modified);
+        }
+        out_file.set_times(times)?;
+        out_file.set_permissions(metadata.permissions())?;
+    }
+    out_file.sync_all()
+}
+
+/// Checks the copy at "copy" against the original, errors are reported against the final destination
+fn verify(from: &Path, copy: &Path, to: &Path, verify: Verify) -> Result<(), FileError>
+{
+    let (expected, actual) = match verify
+    {
+        Verify::None => return Ok(()),
+        Verify::Size => {
+            let size = |path: &Path| std::fs::metadata(path).map(|metadata| format!("{} bytes", metadata.len()));
+            (size(from).map_err(|error| FileError::from_io(from, error))?, size(copy).map_err(|error| FileError::from_io(to, error))?)
+        }
+        Verify::Hash(algorithm) => (
+            hash_path(from, &[algorithm]).map_err(|error| FileError::from_io(from, error))?.remove(0),
+            hash_path(copy, &[algorithm]).map_err(|error| FileError::from_io(to, error))?.remove(0),
+        ),
+    };
+
+    if expected != actual
+    {
+        return Err(FileError::Checksum { path: to.to_path_buf(), expected, actual });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::TestDir;
+
+    #[test]
+    fn copy_and_move() {
+
+        let dir = TestDir::new("copy");
+        std::fs::create_dir_all(dir.join("backup")).unwrap();
+        let path = dir.join("data.bin");
+        let contents: Vec<u8> = (0..3 * 1024 * 1024).map(|index| (index % 251) as u8).collect();
+        std::fs::write(&path, &contents).unwrap();
+        let old = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(old).unwrap();
+
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+
+        // Copying into a directory keeps the name, the modified time and the permissions
+        let reports = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = reports.clone();
+        let mut options = CopyOptions::new();
+        options.verify(Verify::Hash(HashAlgorithm::Sha256)).on_progress(move |progress| seen.lock().unwrap().push(progress.copied));
+        let report = file.copy_to(dir.join("backup"), &options).unwrap();
+        let copy = dir.join("backup").join("data.bin");
+        assert_eq!(report, CopyReport { from: path.clone(), to: copy.clone(), size: contents.len() as u64, action: CopyAction::Copied });
+        assert_eq!(std::fs::read(&copy).unwrap(), contents);
+        assert_eq!(std::fs::metadata(&copy).unwrap().modified().unwrap(), old);
+        assert_eq!(reports.lock().unwrap().last(), Some(&(contents.len() as u64)));
+        assert!(reports.lock().unwrap().len() > 2);
+
+        // Overwrite policies
+        match file.copy_to(&copy, &CopyOptions::new()).unwrap_err()
+        {
+            FileError::Io { path: error_path, source } => {
+                assert_eq!(error_path, copy);
+                assert_eq!(source.kind(), std::io::ErrorKind::AlreadyExists);
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+        assert!(file.copy_to(&path, CopyOptions::new().overwrite(Overwrite::Always)).is_err());
+        assert_eq!(file.copy_to(&copy, CopyOptions::new().overwrite(Overwrite::IfNewer)).unwrap().action, CopyAction::Skipped);
+        std::fs::write(&copy, "stale").unwrap();
+        std::fs::File::options().write(true).open(&copy).unwrap().set_modified(old - std::time::Duration::from_secs(60)).unwrap();
+        assert_eq!(file.copy_to(&copy, CopyOptions::new().overwrite(Overwrite::IfNewer).verify(Verify::Size)).unwrap().action, CopyAction::Copied);
+        assert_eq!(std::fs::read(&copy).unwrap(), contents);
+
+        // Moving renames, and the File follows the file
+        let moved = dir.join("moved.bin");
+        let report = file.move_to(&moved, &CopyOptions::new()).unwrap();
+        assert_eq!(report.action, CopyAction::Renamed);
+        assert!(!path.exists());
+        assert_eq!(file.size().unwrap(), contents.len() as u64);
+        assert!(file.copy_to(dir.join("missing").join("data.bin"), &CopyOptions::new()).is_err());
+        assert!(matches!(File::new().set_file(path.to_str().unwrap()).copy_to(&copy, &CopyOptions::new()), Err(FileError::NotFound { .. })));
+    }
+}
@@ -0,0 +1,165 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::{File, FileError};
+
+impl File
+{
+    /// Creates the file with the contents, failing if it already exists.
+    /// The contents are written in the encoding set with "set_encoding" (UTF-8 if none was set), UTF-16 and UTF-32 get a BOM.
+    /// Text with characters the encoding can't represent is a FileError::Encoding, nothing is written.
+    /// # Examples
+    /// ```no_run
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// let result = file.set_file("C:\\temp\\new.txt").create_new("first line\n");
+    /// ```
+    pub fn create_new(&mut self, contents: &str) -> Result<(), FileError>
+    {
+        let bytes = self.encode_contents(contents).map_err(|error| self.io_error(error))?;
+        std::fs::OpenOptions::new().write(true).create_new(true).open(&self.file)
+            .and_then(|mut out_file| {
+                out_file.write_all(&bytes)?;
+                out_file.flush()
+            })
+            .map_err(|error| self.io_error(error))
+    }
+
+    /// Creates the file with the contents, replacing anything that was in it before
+    /// # Examples
+    /// ```no_run
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// let result = file.set_file("C:\\temp\\new.txt").is_utf16le().create_or_truncate("first line\n");
+    /// ```
+    pub fn create_or_truncate(&mut self, contents: &str) -> Result<(), FileError>
+    {
+        let bytes = self.encode_contents(contents).map_err(|error| self.io_error(error))?;
+        std::fs::File::create(&self.file)
+            .and_then(|mut out_file| {
+                out_file.write_all(&bytes)?;
+                out_file.flush()
+            })
+            .map_err(|error| self.io_error(error))
+    }
+
+    /// Replaces the contents of the file without ever leaving it half written:
+    /// the contents go to a temporary file next to it, which is then renamed over the file.
+    /// Keeps the encoding (and permissions) of an existing file unless "set_encoding" was used.
+    /// # Examples
+    /// ```no_run
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// let result = file.set_file("C:\\temp\\settings.ini").write_atomic("[general]\nenabled=true\n");
+    /// ```
+    pub fn write_atomic(&mut self, contents: &str) -> Result<(), FileError>
+    {
+        let bytes = self.encode_contents(contents).map_err(|error| self.io_error(error))?;
+        let (temp_path, out_file) = create_temp(Path::new(&self.file)).map_err(|error| self.io_error(error))?;
+
+        let written = Self::write_temp(out_file, &bytes);
+        if let Err(error) = written
+        {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(FileError::from_io(&temp_path, error));
+        }
+
+        if let Ok(metadata) = std::fs::metadata(&self.file)
+        {
+            let _ = std::fs::set_permissions(&temp_path, metadata.permissions());
+        }
+
+        let renamed = std::fs::rename(&temp_path, &self.file);
+        if renamed.is_err()
+        {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+        renamed.map_err(|error| self.io_error(error))
+    }
+
+    /// The contents as bytes in the encoding the file is written with, including the BOM if it needs one
+    pub(crate) fn encode_contents(&self, contents: &str) -> io::Result<Vec<u8>>
+    {
+        let encoding = self.write_encoding()?;
+        let mut bytes = encoding.bom().to_vec();
+
+        // A UTF-8 BOM is never added, but one that is already there is kept
+        if bytes.is_empty() && self.has_utf8_bom()
+        {
+            bytes.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+        }
+
+        bytes.extend(self.encode_text(encoding, contents)?);
+        Ok(bytes)
+    }
+
+    fn has_utf8_bom(&self) -> bool
+    {
+        let mut start = [0u8; 3];
+        let file = std::fs::File::open(&self.file);
+        file.and_then(|mut file| io::Read::read_exact(&mut file, &mut start)).is_ok() && start == [0xEF, 0xBB, 0xBF]
+    }
+
+    fn write_temp(mut out_file: std::fs::File, bytes: &[u8]) -> io::Result<()>
+    {
+        out_file.write_all(bytes)?;
+        out_file.sync_all()
+    }
+}
+
+static TEMP_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Creates a hidden file in the same directory as the path, so renaming it over the path never has to cross filesystems.
+/// Every call gets a file of its own, even when several threads are writing to the same path.
+pub(super) fn create_temp(path: &Path) -> io::Result<(PathBuf, std::fs::File)>
+{
+    loop
+    {
+        let mut name = std::ffi::OsString::from(".");
+        name.push(path.file_name().unwrap_or_default());
+        name.push(format!(".{}.{}.tmp", std::process::id(), TEMP_COUNT.fetch_add(1, Ordering::Relaxed)));
+        let temp_path = path.with_file_name(name);
+
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&temp_path)
+        {
+            Ok(out_file) => return Ok((temp_path, out_file)),
+            // Left behind by another process that had the same id
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::TestDir;
+
+    #[test]
+    fn atomic_writes_from_threads() {
+
+        let dir = TestDir::new("atomic");
+        let path = dir.join("settings.ini");
+
+        let writers: Vec<_> = (0..8).map(|writer| {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let contents = format!("writer={}\n", writer).repeat(10_000);
+                let mut file = File::new();
+                file.set_file(path.to_str().unwrap());
+                for _ in 0..5
+                {
+                    file.write_atomic(&contents).unwrap();
+                }
+            })
+        }).collect();
+        for writer in writers
+        {
+            writer.join().unwrap();
+        }
+
+        // Whichever write landed last, it landed whole, and no temporary files were left behind
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents, contents.lines().next().map(|line| format!("{}\n", line)).unwrap().repeat(10_000));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
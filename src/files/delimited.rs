@@ -0,0 +1,305 @@
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::{File, FileError};
+
+/// How File::read_csv and File::write_csv split up rows.
+/// Defaults to comma separated, double quoted fields, with a header row.
+/// # Examples
+/// ```
+/// use davids_awesome_library::files::CsvOptions;
+///
+/// // Tab separated, without a header row
+/// let mut options = CsvOptions::new();
+/// options.delimiter(b'\t').has_headers(false);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CsvOptions
+{
+    delimiter: u8,
+    quote: u8,
+    has_headers: bool,
+    trim: bool,
+    flexible: bool,
+    crlf: bool,
+}
+
+impl Default for CsvOptions
+{
+    fn default() -> Self {
+        CsvOptions { delimiter: b',', quote: b'"', has_headers: true, trim: false, flexible: false, crlf: false }
+    }
+}
+
+impl CsvOptions
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// The character between fields, eg. b';' or b'\t'
+    pub fn delimiter(&mut self, delimiter: u8) -> &mut Self
+    {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// The character fields containing the delimiter or line endings are wrapped in
+    pub fn quote(&mut self, quote: u8) -> &mut Self
+    {
+        self.quote = quote;
+        self
+    }
+
+    /// Whether the first row is the column names
+    pub fn has_headers(&mut self, has_headers: bool) -> &mut Self
+    {
+        self.has_headers = has_headers;
+        self
+    }
+
+    /// Removes spaces around every field (and header) when reading
+    pub fn trim(&mut self, trim: bool) -> &mut Self
+    {
+        self.trim = trim;
+        self
+    }
+
+    /// Allows rows with a different number of fields, otherwise they are an error
+    pub fn flexible(&mut self, flexible: bool) -> &mut Self
+    {
+        self.flexible = flexible;
+        self
+    }
+
+    /// Ends rows with "\r\n" when writing, instead of "\n"
+    pub fn crlf(&mut self, crlf: bool) -> &mut Self
+    {
+        self.crlf = crlf;
+        self
+    }
+
+    fn reader<'a>(&self, text: &'a str) -> csv::Reader<&'a [u8]>
+    {
+        csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .has_headers(self.has_headers)
+            .trim(if self.trim { csv::Trim::All } else { csv::Trim::None })
+            .flexible(self.flexible)
+            .from_reader(text.as_bytes())
+    }
+
+    fn writer(&self) -> csv::Writer<Vec<u8>>
+    {
+        csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .has_headers(self.has_headers)
+            .flexible(self.flexible)
+            .terminator(if self.crlf { csv::Terminator::CRLF } else { csv::Terminator::Any(b'\n') })
+            .from_writer(Vec::new())
+    }
+}
+
+/// The rows of a CSV file as text, see File::read_csv
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsvTable
+{
+    /// The column names, empty if the file has no header row
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl CsvTable
+{
+    pub fn new(headers: &[&str]) -> Self
+    {
+        CsvTable { headers: headers.iter().map(|header| header.to_string()).collect(), rows: Vec::new() }
+    }
+
+    /// The index of the column with the header (matched exactly)
+    pub fn column(&self, header: &str) -> Option<usize>
+    {
+        self.headers.iter().position(|name| name == header)
+    }
+
+    /// The field in the row (starting at 0, not counting the header row) and the column with the header
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::files::CsvTable;
+    ///
+    /// let mut table = CsvTable::new(&["name", "size"]);
+    /// table.push(&["installer.exe", "1024"]);
+    /// assert_eq!(table.get(0, "size"), Some("1024"));
+    /// ```
+    pub fn get(&self, row: usize, header: &str) -> Option<&str>
+    {
+        let column = self.column(header)?;
+        self.rows.get(row)?.get(column).map(|field| field.as_str())
+    }
+
+    /// Adds a row to the end
+    pub fn push(&mut self, row: &[&str]) -> &mut Self
+    {
+        self.rows.push(row.iter().map(|field| field.to_string()).collect());
+        self
+    }
+}
+
+impl File
+{
+    /// Reads the file as CSV, handling quoted fields (including ones spread over several lines)
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::files::{CsvOptions, File};
+    ///
+    /// let mut file = File::new();
+    /// if let Ok(table) = file.set_file("C:\\temp\\export.csv").read_csv(&CsvOptions::new())
+    /// {
+    ///     for row in 0..table.rows.len()
+    ///     {
+    ///         println!("{:?}", table.get(row, "name"));
+    ///     }
+    /// }
+    /// ```
+    pub fn read_csv(&mut self, options: &CsvOptions) -> Result<CsvTable, FileError>
+    {
+        let text = self.read_text()?;
+        let mut reader = options.reader(&text);
+
+        let mut table = CsvTable::default();
+        if options.has_headers
+        {
+            table.headers = reader.headers().map_err(|source| self.csv_error(source))?.iter().map(|header| header.to_string()).collect();
+        }
+        for record in reader.records()
+        {
+            let record = record.map_err(|source| self.csv_error(source))?;
+            table.rows.push(record.iter().map(|field| field.to_string()).collect());
+        }
+        Ok(table)
+    }
+
+    /// Reads the file as CSV into any type that implements serde's Deserialize, matching the columns to fields by their header
+    /// (or by position without headers)
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::files::{CsvOptions, File};
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Download
+    /// {
+    ///     name: String,
+    ///     size: u64,
+    /// }
+    ///
+    /// let mut file = File::new();
+    /// let downloads: Result<Vec<Download>, _> = file.set_file("C:\\temp\\export.csv").read_csv_as(&CsvOptions::new());
+    /// ```
+    pub fn read_csv_as<T: DeserializeOwned>(&mut self, options: &CsvOptions) -> Result<Vec<T>, FileError>
+    {
+        let text = self.read_text()?;
+        options.reader(&text)
+            .deserialize()
+            .collect::<Result<Vec<T>, csv::Error>>()
+            .map_err(|source| self.csv_error(source))
+    }
+
+    /// Writes the table to the file (quoting fields where needed), replacing it in one go.
+    /// The header row is only written if the options have headers.
+    /// # Examples
+    /// ```no_run
+    /// use davids_awesome_library::files::{CsvOptions, CsvTable, File};
+    ///
+    /// let mut table = CsvTable::new(&["name", "size"]);
+    /// table.push(&["installer.exe", "1024"]);
+    ///
+    /// let mut file = File::new();
+    /// let result = file.set_file("C:\\temp\\export.csv").write_csv(&table, &CsvOptions::new());
+    /// ```
+    pub fn write_csv(&mut self, table: &CsvTable, options: &CsvOptions) -> Result<(), FileError>
+    {
+        let mut writer = options.writer();
+        if options.has_headers
+        {
+            writer.write_record(&table.headers).map_err(|source| self.csv_error(source))?;
+        }
+        for row in &table.rows
+        {
+            writer.write_record(row).map_err(|source| self.csv_error(source))?;
+        }
+        self.write_csv_bytes(writer)
+    }
+
+    /// Writes rows of any type that implements serde's Serialize, the header row comes from the field names
+    pub fn write_csv_from<T: Serialize>(&mut self, rows: &[T], options: &CsvOptions) -> Result<(), FileError>
+    {
+        let mut writer = options.writer();
+        for row in rows
+        {
+            writer.serialize(row).map_err(|source| self.csv_error(source))?;
+        }
+        self.write_csv_bytes(writer)
+    }
+
+    fn write_csv_bytes(&mut self, writer: csv::Writer<Vec<u8>>) -> Result<(), FileError>
+    {
+        let bytes = writer.into_inner().map_err(|error| self.csv_error(error.into_error().into()))?;
+        // Only ever holds what was given as strings
+        self.write_atomic(&String::from_utf8_lossy(&bytes))
+    }
+
+    fn csv_error(&self, source: csv::Error) -> FileError
+    {
+        FileError::Csv { path: PathBuf::from(&self.file), source }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::TestDir;
+
+    #[test]
+    fn quoting_and_typed_rows() {
+
+        let dir = TestDir::new("csv");
+
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Row
+        {
+            name: String,
+            size: u64,
+        }
+
+        let path = dir.join("export.csv");
+        std::fs::write(&path, "name;size\n\"a; b\";1\n\"say \"\"hi\"\"\nthere\";2\n").unwrap();
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+        let mut options = CsvOptions::new();
+        options.delimiter(b';');
+        let mut table = file.read_csv(&options).unwrap();
+        assert_eq!(table.headers, vec!["name", "size"]);
+        assert_eq!(table.get(0, "name"), Some("a; b"));
+        assert_eq!(table.get(1, "name"), Some("say \"hi\"\nthere"));
+        assert_eq!(file.read_csv_as::<Row>(&options).unwrap()[1], Row { name: "say \"hi\"\nthere".to_string(), size: 2 });
+
+        table.push(&["c", "3"]);
+        file.write_csv(&table, &options).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "name;size\n\"a; b\";1\n\"say \"\"hi\"\"\nthere\";2\nc;3\n");
+
+        file.write_csv_from(&[Row { name: "d".to_string(), size: 4 }], CsvOptions::new().crlf(true)).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "name,size\r\nd,4\r\n");
+
+        std::fs::write(&path, "name,size\nx,not a number\n").unwrap();
+        assert!(matches!(file.read_csv_as::<Row>(&CsvOptions::new()), Err(FileError::Csv { .. })));
+        std::fs::write(&path, "name,size\nx\n").unwrap();
+        assert!(matches!(file.read_csv(&CsvOptions::new()), Err(FileError::Csv { .. })));
+        assert_eq!(file.read_csv(CsvOptions::new().flexible(true)).unwrap().rows, vec![vec!["x".to_string()]]);
+    }
+}
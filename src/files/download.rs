@@ -1,42 +1,648 @@
-use std::io;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use super::File;
+use super::hash::{hash_path, HashAlgorithm};
+use super::{File, FileError};
+
+/// The callback set with DownloadOptions::on_progress
+type ProgressCallback = Arc<dyn Fn(&Progress) + Send + Sync>;
+
+/// Settings for File::download_with
+#[derive(Clone, Default)]
+pub struct DownloadOptions
+{
+    pub(super) resume: bool,
+    pub(super) sha256: Option<String>,
+    pub(super) progress: Option<ProgressCallback>,
+    pub(super) connect_timeout: Option<Duration>,
+    pub(super) read_timeout: Option<Duration>,
+    pub(super) retries: u32,
+    pub(super) retry_delay: Option<Duration>,
+    pub(super) headers: Vec<(String, String)>,
+}
+
+/// How far along a download is, handed to the callback set with DownloadOptions::on_progress
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress
+{
+    /// Bytes in the file so far, including anything picked up from a resumed download
+    pub downloaded: u64,
+    /// The full size of the file, when the server sent a Content-Length
+    pub total: Option<u64>,
+    /// The average speed of the current attempt
+    pub bytes_per_second: f64,
+}
+
+impl std::fmt::Debug for DownloadOptions
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        f.debug_struct("DownloadOptions")
+            .field("resume", &self.resume)
+            .field("sha256", &self.sha256)
+            .field("progress", &self.progress.is_some())
+            .field("connect_timeout", &self.connect_timeout)
+            .field("read_timeout", &self.read_timeout)
+            .field("retries", &self.retries)
+            .field("retry_delay", &self.retry_delay)
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
+impl DownloadOptions
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Continue a previous download that was cut off, using the ".part" file it left behind and an HTTP Range request.
+    /// The ".part" file is also kept if this download gets cut off.
+    /// If the file on the server changed since (going by its ETag or Last-Modified) it's downloaded again from the start.
+    pub fn resume(&mut self, enabled: bool) -> &mut Self
+    {
+        self.resume = enabled;
+        self
+    }
+
+    /// Check the download against a SHA-256 checksum (in hex) before it replaces the file
+    pub fn sha256(&mut self, checksum: &str) -> &mut Self
+    {
+        self.sha256 = Some(checksum.trim().to_ascii_lowercase());
+        self
+    }
+
+    /// Called every time a chunk of the file has been written out
+    pub fn on_progress<F: Fn(&Progress) + Send + Sync + 'static>(&mut self, callback: F) -> &mut Self
+    {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// How long to wait for the connection to the server to be made, no limit by default
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self
+    {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// How long to wait for the server to answer or send the next chunk, 30 seconds by default (reqwest's default)
+    pub fn read_timeout(&mut self, timeout: Duration) -> &mut Self
+    {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Try again this many times after a connection error, a timeout or a 408/429/5xx status,
+    /// waiting twice as long before each retry starting at the delay (1 second by default).
+    /// A retry picks up where the previous attempt was cut off.
+    pub fn retries(&mut self, count: u32, delay: Duration) -> &mut Self
+    {
+        self.retries = count;
+        self.retry_delay = Some(delay);
+        self
+    }
+
+    /// Adds a header to the request (eg. "Authorization")
+    pub fn header(&mut self, name: &str, value: &str) -> &mut Self
+    {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Sets the User-Agent header sent with the request
+    pub fn user_agent(&mut self, user_agent: &str) -> &mut Self
+    {
+        self.header("User-Agent", user_agent)
+    }
+}
+
+/// Whether a failed attempt is worth trying again.
+/// The typed errors (FileError::Download, FileError::Http) travel inside the io::Error until finish_download takes them back out.
+pub(super) enum Failure
+{
+    Transient(io::Error),
+    Fatal(io::Error),
+}
+
+impl Failure
+{
+    /// The request could not be sent or its body could not be read, only a malformed request is not worth retrying
+    pub(super) fn from_request(error: reqwest::Error, uri: &str, path: &Path) -> Self
+    {
+        let kind = if error.is_timeout() { io::ErrorKind::TimedOut } else { io::ErrorKind::Other };
+        let is_builder = error.is_builder();
+        let failure = download_error(path, uri, io::Error::new(kind, error));
+        if is_builder
+        {
+            return Failure::Fatal(failure);
+        }
+        Failure::Transient(failure)
+    }
+
+    /// The server answered with an error status, which is worth retrying for timeouts, rate limits and server errors
+    pub(super) fn from_status(status: reqwest::StatusCode, uri: &str, path: &Path) -> Self
+    {
+        let failure = FileError::Http { path: path.to_path_buf(), url: uri.to_string(), status: status.as_u16() }.into();
+        let transient = status.is_server_error() || status == reqwest::StatusCode::REQUEST_TIMEOUT || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+        if transient { Failure::Transient(failure) } else { Failure::Fatal(failure) }
+    }
+
+    /// The connection closed before everything the server promised came through
+    pub(super) fn cut_off(uri: &str, path: &Path) -> Self
+    {
+        Failure::Transient(download_error(path, uri, io::Error::new(io::ErrorKind::UnexpectedEof, "Download Was Cut Off")))
+    }
+}
+
+fn download_error(path: &Path, uri: &str, source: io::Error) -> io::Error
+{
+    FileError::Download { path: path.to_path_buf(), url: uri.to_string(), source }.into()
+}
+
+impl DownloadOptions
+{
+    /// The custom headers as a map for the client
+    pub(super) fn header_map(&self) -> io::Result<reqwest::header::HeaderMap>
+    {
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in &self.headers
+        {
+            let name = reqwest::header::HeaderName::from_bytes(name.as_bytes());
+            let value = reqwest::header::HeaderValue::from_str(value);
+            if name.is_err() || value.is_err()
+            {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid Header"));
+            }
+            headers.append(name.unwrap(), value.unwrap());
+        }
+        Ok(headers)
+    }
+
+    /// Hands the progress to the callback, if there is one
+    pub(super) fn report(&self, downloaded: u64, total: Option<u64>, received: u64, started: Instant)
+    {
+        if let Some(progress) = &self.progress
+        {
+            let elapsed = started.elapsed().as_secs_f64();
+            progress(&Progress {
+                downloaded,
+                total,
+                bytes_per_second: if elapsed > 0.0 { received as f64 / elapsed } else { 0.0 },
+            });
+        }
+    }
+}
 
 impl File
 {
     /// Downloads  file from a URL and downloads it to the location specified by 'set_file(...)'
-    pub fn download_from(&mut self, uri: &str) -> std::io::Result<()>
+    /// The file is only replaced once the download finished, it fails on HTTP error statuses (eg. 404)
+    /// # Examples
+    /// ```no_run
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// let result = file.set_file("C:\\temp\\installer.exe").download_from("https://example.com/installer.exe");
+    /// ```
+    pub fn download_from(&mut self, uri: &str) -> Result<(), FileError>
+    {
+        self.download_with(uri, &DownloadOptions::new())
+    }
+
+    /// Downloads a file from a URL to the location specified by 'set_file(...)', with resuming, retries, progress and checksum verification.
+    /// The download goes to "<file>.part" first and is renamed over the file once it is complete (and verified).
+    /// # Examples
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use davids_awesome_library::files::{DownloadOptions, File};
+    ///
+    /// let mut file = File::new();
+    /// let result = file.set_file("C:\\temp\\installer.exe").download_with(
+    ///     "https://example.com/installer.exe",
+    ///     DownloadOptions::new()
+    ///         .resume(true)
+    ///         .retries(3, Duration::from_secs(1))
+    ///         .connect_timeout(Duration::from_secs(10))
+    ///         .header("Authorization", "Bearer token")
+    ///         .on_progress(|progress| println!("{} of {:?} bytes", progress.downloaded, progress.total))
+    ///         .sha256("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"),
+    /// );
+    /// ```
+    pub fn download_with(&mut self, uri: &str, options: &DownloadOptions) -> Result<(), FileError>
     {
-        // Set the file path we will use from the Struct
-        let file_path = self.file.clone();
+        let path = PathBuf::from(&self.file);
+        let client = Self::download_client(options).map_err(|error| self.io_error(error))?;
 
-        // This will download the file to memory
-        let response = reqwest::blocking::get(uri);
-        
-        // Make sure the download succeeded and did not return an error
-        if response.is_err()
+        let mut attempts = Attempts::new(options);
+        let downloaded = loop
         {
-            return Err(io::Error::new(io::ErrorKind::InvalidData, "Could Download File"));
-        }
+            match Self::download_part(&client, uri, options, &path, attempts.resume())
+            {
+                Ok(()) => break Ok(()),
+                Err(failure) => match attempts.retry_after(failure)
+                {
+                    Ok(delay) => std::thread::sleep(delay),
+                    Err(error) => break Err(error),
+                },
+            }
+        };
 
-        // Set assign a variable to the downloaded bytes
-        let mut data_bytes = response.unwrap();
+        finish_download(downloaded, options, &path)
+    }
 
-        // Remove the previous file (if it exists)
-        if std::path::Path::exists(&std::path::Path::new(&file_path))
+    fn download_client(options: &DownloadOptions) -> io::Result<reqwest::blocking::Client>
+    {
+        let mut builder = reqwest::blocking::Client::builder().default_headers(options.header_map()?);
+        if let Some(timeout) = options.connect_timeout
         {
-            let _ = std::fs::remove_file(file_path.clone());
+            builder = builder.connect_timeout(timeout);
         }
-        
-        // Create the file
-        let mut out_file = std::fs::File::create(file_path)?;
+        if let Some(timeout) = options.read_timeout
+        {
+            builder = builder.timeout(timeout);
+        }
+        builder.build().map_err(|error| io::Error::other(format!("Could Not Create Client: {}", error)))
+    }
+
+    /// Downloads into the ".part" file, continuing where it left off if resuming
+    fn download_part(client: &reqwest::blocking::Client, uri: &str, options: &DownloadOptions, path: &Path, resume: bool) -> Result<(), Failure>
+    {
+        let part = Part::new(path, resume);
+        let mut response = client.get(uri).headers(part.request_headers()).send().map_err(|error| Failure::from_request(error, uri, path))?;
+
+        let mut transfer = match part.answer(options, uri, response.status(), response.headers(), response.content_length())?
+        {
+            Answer::Complete => return Ok(()),
+            Answer::Restart => return Self::download_part(client, uri, options, path, false),
+            Answer::Write(transfer) => transfer,
+        };
+        let mut out_file = std::fs::OpenOptions::new().create(true).write(true).append(transfer.append).truncate(!transfer.append).open(&part.part_path).map_err(Failure::Fatal)?;
 
         // Write the bytes to the file
-        let _ = io::copy(&mut data_bytes, &mut out_file)?;
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop
+        {
+            // A connection dropped part way through can be picked up again
+            let read = response.read(&mut buffer).map_err(|error| Failure::Transient(download_error(path, uri, error)))?;
+            if read == 0
+            {
+                break;
+            }
+            out_file.write_all(&buffer[..read]).map_err(Failure::Fatal)?;
+            transfer.written(read);
+        }
+        out_file.sync_all().map_err(Failure::Fatal)?;
+
+        transfer.finish(uri, path)
+    }
+}
+
+/// Counts the attempts at a download, and works out how long to wait before the next one.
+/// Shared by the blocking and async downloads, which only differ in how they wait.
+pub(super) struct Attempts<'a>
+{
+    options: &'a DownloadOptions,
+    attempt: u32,
+    delay: Duration,
+}
+
+impl<'a> Attempts<'a>
+{
+    pub(super) fn new(options: &'a DownloadOptions) -> Self
+    {
+        Attempts { options, attempt: 0, delay: options.retry_delay.unwrap_or(Duration::from_secs(1)) }
+    }
+
+    /// Whether the next attempt continues from the ".part" file, retries always continue from what the previous attempt wrote
+    pub(super) fn resume(&self) -> bool
+    {
+        self.options.resume || self.attempt > 0
+    }
 
-        // Return 'ok' (success)
+    /// How long to wait before trying again after the failure (twice as long as last time),
+    /// or the error to give up with if it isn't worth retrying or there are no retries left
+    pub(super) fn retry_after(&mut self, failure: Failure) -> io::Result<Duration>
+    {
+        match failure
+        {
+            Failure::Transient(_) if self.attempt < self.options.retries => {
+                self.attempt += 1;
+                let delay = self.delay;
+                self.delay *= 2;
+                Ok(delay)
+            }
+            Failure::Transient(error) | Failure::Fatal(error) => Err(error),
+        }
+    }
+}
+
+/// The ".part" file one attempt downloads into, and how much of it can be kept when asking for the rest
+pub(super) struct Part
+{
+    path: PathBuf,
+    pub(super) part_path: PathBuf,
+    existing: u64,
+    /// The ETag (or Last-Modified) of the file the ".part" was downloaded from, so a changed file isn't continued
+    validator: Option<String>,
+}
+
+/// What an attempt does with the answer from the server
+pub(super) enum Answer<'a>
+{
+    /// Everything was already downloaded last time
+    Complete,
+    /// The ".part" file can't be continued, the whole file has to be asked for again
+    Restart,
+    /// The body goes into the ".part" file
+    Write(Transfer<'a>),
+}
+
+impl Part
+{
+    pub(super) fn new(path: &Path, resume: bool) -> Self
+    {
+        let part_path = part_path(path);
+        let existing = if resume { std::fs::metadata(&part_path).map(|metadata| metadata.len()).unwrap_or(0) } else { 0 };
+        let validator = if existing > 0 { std::fs::read_to_string(validator_path(path)).ok() } else { None };
+        Part { path: path.to_path_buf(), part_path, existing, validator }
+    }
+
+    /// The Range header asking for everything after what's already in the ".part" file,
+    /// with an If-Range so the server sends the whole file instead if it changed since
+    pub(super) fn request_headers(&self) -> reqwest::header::HeaderMap
+    {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if self.existing > 0
+        {
+            headers.insert(reqwest::header::RANGE, reqwest::header::HeaderValue::from_str(&format!("bytes={}-", self.existing)).unwrap());
+            if let Some(value) = self.validator.as_ref().and_then(|validator| reqwest::header::HeaderValue::from_str(validator).ok())
+            {
+                headers.insert(reqwest::header::IF_RANGE, value);
+            }
+        }
+        headers
+    }
+
+    /// Decides what to do with the answer to the request, failing on error statuses
+    pub(super) fn answer<'a>(&self, options: &'a DownloadOptions, uri: &str, status: reqwest::StatusCode, headers: &reqwest::header::HeaderMap, content_length: Option<u64>) -> Result<Answer<'a>, Failure>
+    {
+        if self.existing > 0 && status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE
+        {
+            // Everything was already downloaded last time, otherwise the ".part" is stale and is started over
+            if part_is_complete(headers, self.existing)
+            {
+                return Ok(Answer::Complete);
+            }
+            return Ok(Answer::Restart);
+        }
+        if !status.is_success()
+        {
+            return Err(Failure::from_status(status, uri, &self.path));
+        }
+
+        // A server that ignores the range (or has a newer file) sends the whole file again
+        let append = self.existing > 0 && status == reqwest::StatusCode::PARTIAL_CONTENT;
+        if append && range_start(headers) != Some(self.existing)
+        {
+            // Anything other than the rest of the file can't be added to the ".part"
+            return Ok(Answer::Restart);
+        }
+        if !append
+        {
+            save_validator(&self.path, headers);
+        }
+
+        let downloaded = if append { self.existing } else { 0 };
+        Ok(Answer::Write(Transfer {
+            options,
+            append,
+            downloaded,
+            total: content_length.map(|length| length + downloaded),
+            received: 0,
+            started: Instant::now(),
+        }))
+    }
+}
+
+/// Keeps count of what one attempt wrote, for the progress callback and to tell if the connection was cut off
+pub(super) struct Transfer<'a>
+{
+    options: &'a DownloadOptions,
+    /// Whether the body goes after what's already in the ".part" file, instead of replacing it
+    pub(super) append: bool,
+    downloaded: u64,
+    total: Option<u64>,
+    received: u64,
+    started: Instant,
+}
+
+impl Transfer<'_>
+{
+    pub(super) fn written(&mut self, bytes: usize)
+    {
+        self.downloaded += bytes as u64;
+        self.received += bytes as u64;
+        self.options.report(self.downloaded, self.total, self.received, self.started);
+    }
+
+    /// Fails if less came through than the server said it would send
+    pub(super) fn finish(&self, uri: &str, path: &Path) -> Result<(), Failure>
+    {
+        if self.total.is_some_and(|total| self.downloaded < total)
+        {
+            return Err(Failure::cut_off(uri, path));
+        }
         Ok(())
     }
+}
+
+/// Whether a 416 answer to a range request means the ".part" file already holds the whole file,
+/// the server gives the full size as "Content-Range: bytes */<size>"
+fn part_is_complete(headers: &reqwest::header::HeaderMap, existing: u64) -> bool
+{
+    headers.get(reqwest::header::CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().strip_prefix("bytes */"))
+        .and_then(|size| size.trim().parse::<u64>().ok())
+        == Some(existing)
+}
 
+/// Where the first byte of a 206 answer goes, from "Content-Range: bytes <start>-<end>/<size>"
+fn range_start(headers: &reqwest::header::HeaderMap) -> Option<u64>
+{
+    let range = headers.get(reqwest::header::CONTENT_RANGE)?.to_str().ok()?.trim().strip_prefix("bytes ")?;
+    range.split_once('-')?.0.trim().parse().ok()
 }
 
+/// Keeps the ETag of a download that starts from the beginning next to its ".part" file, or its Last-Modified without one.
+/// Weak ETags ("W/...") can't be used with If-Range.
+fn save_validator(path: &Path, headers: &reqwest::header::HeaderMap)
+{
+    let etag = headers.get(reqwest::header::ETAG).filter(|etag| !etag.as_bytes().starts_with(b"W/"));
+    let validator = etag.or_else(|| headers.get(reqwest::header::LAST_MODIFIED)).and_then(|value| value.to_str().ok());
+    let _ = match validator
+    {
+        Some(validator) => std::fs::write(validator_path(path), validator),
+        None => std::fs::remove_file(validator_path(path)),
+    };
+}
+
+/// Removes the ".part" file and the validator kept next to it
+fn remove_part(path: &Path)
+{
+    let _ = std::fs::remove_file(part_path(path));
+    let _ = std::fs::remove_file(validator_path(path));
+}
+
+/// Where the download goes until it is complete, "installer.exe" downloads to "installer.exe.part"
+fn part_path(path: &Path) -> PathBuf
+{
+    let mut part_path = path.as_os_str().to_os_string();
+    part_path.push(".part");
+    PathBuf::from(part_path)
+}
+
+/// Where the validator of the ".part" file is kept, "installer.exe" keeps it in "installer.exe.part.validator"
+fn validator_path(path: &Path) -> PathBuf
+{
+    let mut validator_path = part_path(path).into_os_string();
+    validator_path.push(".validator");
+    PathBuf::from(validator_path)
+}
+
+/// Verifies a finished download and moves it over the file at "path", or cleans up after a failed one
+pub(super) fn finish_download(downloaded: io::Result<()>, options: &DownloadOptions, path: &Path) -> Result<(), FileError>
+{
+    let part_path = &part_path(path);
+    if let Err(error) = downloaded
+    {
+        // Without resuming a partial download is of no use to anyone
+        if !options.resume
+        {
+            remove_part(path);
+        }
+        return Err(FileError::from_io(part_path, error));
+    }
+
+    if let Some(expected) = &options.sha256
+    {
+        let actual = hash_path(part_path, &[HashAlgorithm::Sha256]).map_err(|error| FileError::from_io(part_path, error))?.remove(0);
+        if &actual != expected
+        {
+            remove_part(path);
+            return Err(FileError::Checksum { path: path.to_path_buf(), expected: expected.clone(), actual });
+        }
+    }
+
+    std::fs::rename(part_path, path).map_err(|error| FileError::from_io(path, error))?;
+    let _ = std::fs::remove_file(validator_path(path));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::{serve, TestDir};
+
+    #[test]
+    fn download_verify_and_resume() {
+
+        let body: Vec<u8> = (0..200_000u32).map(|count| (count % 251) as u8).collect();
+        let url = serve(body.clone(), 11);
+
+        let dir = TestDir::new("download");
+        let path = dir.join("file.bin");
+        let part = dir.join("file.bin.part");
+        std::fs::write(&path, "previous version").unwrap();
+
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+
+        // A 404 fails without touching the existing file
+        let error = file.download_from(&format!("{}/missing", url)).unwrap_err();
+        assert!(error.to_string().contains("404"));
+        assert!(matches!(error, FileError::Http { status: 404, .. }));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "previous version");
+
+        // A cut off download leaves its ".part" behind when resuming, and picks up from there
+        assert!(file.download_with(&format!("{}/cut", url), DownloadOptions::new().resume(true)).is_err());
+        assert_eq!(std::fs::read(&part).unwrap(), &body[..body.len() / 2]);
+        assert_eq!(std::fs::read_to_string(dir.join("file.bin.part.validator")).unwrap(), "\"v1\"");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "previous version");
+
+        let checksum = {
+            use sha2::Digest;
+            sha2::Sha256::digest(&body).iter().map(|byte| format!("{:02x}", byte)).collect::<String>()
+        };
+        file.download_with(&format!("{}/file", url), DownloadOptions::new().resume(true).sha256(&checksum)).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+        assert!(!part.exists());
+
+        // A ".part" that already has everything is finished without downloading it again
+        std::fs::write(&path, "previous version").unwrap();
+        std::fs::write(&part, &body).unwrap();
+        file.download_with(&format!("{}/file", url), DownloadOptions::new().resume(true)).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+
+        // One that is larger than the file is stale, and is downloaded again from the start
+        std::fs::write(&part, [&body[..], b"left over from something else"].concat()).unwrap();
+        file.download_with(&format!("{}/file", url), DownloadOptions::new().resume(true)).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+        assert!(!part.exists());
+
+        // A 206 for some other range than the rest of the ".part" isn't added to it, the download starts over
+        std::fs::write(&part, &body[..1000]).unwrap();
+        file.download_with(&format!("{}/wrong-range", url), DownloadOptions::new().resume(true)).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+
+        // Neither is a ".part" of a file that changed on the server since, going by the ETag kept next to it
+        std::fs::write(&part, vec![0u8; 1000]).unwrap();
+        std::fs::write(dir.join("file.bin.part.validator"), "\"v0\"").unwrap();
+        file.download_with(&format!("{}/file", url), DownloadOptions::new().resume(true)).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+        assert!(!dir.join("file.bin.part.validator").exists());
+
+        // A checksum mismatch never replaces the file
+        let error = file.download_with(&format!("{}/file", url), DownloadOptions::new().sha256("00")).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+        assert!(!part.exists());
+    }
+
+    #[test]
+    fn download_progress_retries_and_headers() {
+
+        let body: Vec<u8> = (0..150_000u32).map(|count| (count % 241) as u8).collect();
+        let url = serve(body.clone(), 4);
+
+        let dir = TestDir::new("download_retry");
+        let path = dir.join("file.bin");
+
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+
+        // Missing credentials are not worth retrying
+        let error = file.download_with(&format!("{}/private", url), DownloadOptions::new().retries(3, std::time::Duration::from_millis(10))).unwrap_err();
+        assert!(error.to_string().contains("401"));
+
+        // The cut off first attempt is retried from where it stopped, reporting progress along the way
+        let reports = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = reports.clone();
+        file.download_with(&format!("{}/private/cut", url), DownloadOptions::new()
+            .header("Authorization", "Bearer secret")
+            .user_agent("tester")
+            .read_timeout(std::time::Duration::from_secs(5))
+            .retries(2, std::time::Duration::from_millis(10))
+            .on_progress(move |progress| seen.lock().unwrap().push(*progress))).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+
+        let reports = reports.lock().unwrap();
+        let last = reports.last().unwrap();
+        assert_eq!(last.downloaded, body.len() as u64);
+        assert_eq!(last.total, Some(body.len() as u64));
+        assert!(reports.windows(2).all(|pair| pair[0].downloaded < pair[1].downloaded));
+    }
+}
@@ -0,0 +1,202 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use futures::StreamExt;
+use tokio::io::AsyncWriteExt;
+
+use super::download::{finish_download, Answer, Attempts, DownloadOptions, Failure, Part};
+use super::{File, FileError};
+
+/// The outcome of one of the downloads started by download_many
+#[derive(Debug)]
+pub struct DownloadResult
+{
+    pub uri: String,
+    pub path: PathBuf,
+    pub result: Result<(), FileError>,
+}
+
+impl File
+{
+    /// The async version of download_from, for use inside a Tokio runtime where the blocking client would panic
+    /// # Examples
+    /// ```no_run
+    /// # async fn example() {
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// let result = file.set_file("C:\\temp\\installer.exe").download_from_async("https://example.com/installer.exe").await;
+    /// # }
+    /// ```
+    pub async fn download_from_async(&mut self, uri: &str) -> Result<(), FileError>
+    {
+        self.download_with_async(uri, &DownloadOptions::new()).await
+    }
+
+    /// The async version of download_with, taking the same options (resuming, retries, progress, checksums, headers and timeouts)
+    /// # Examples
+    /// ```no_run
+    /// # async fn example() {
+    /// use davids_awesome_library::files::{DownloadOptions, File};
+    ///
+    /// let mut file = File::new();
+    /// let result = file.set_file("C:\\temp\\installer.exe")
+    ///     .download_with_async("https://example.com/installer.exe", DownloadOptions::new().resume(true))
+    ///     .await;
+    /// # }
+    /// ```
+    pub async fn download_with_async(&mut self, uri: &str, options: &DownloadOptions) -> Result<(), FileError>
+    {
+        let client = async_client(options).map_err(|error| self.io_error(error))?;
+        download_to(&client, uri, options, PathBuf::from(&self.file)).await
+    }
+}
+
+/// Downloads every (URL, path) pair, running at most "concurrency" downloads at once.
+/// Every download gets its own result, in the same order they were given, so one failure doesn't stop the rest.
+/// # Examples
+/// ```no_run
+/// # async fn example() {
+/// use davids_awesome_library::files::{download_many, DownloadOptions};
+///
+/// let downloads = vec![
+///     ("https://example.com/one.zip", "C:\\temp\\one.zip"),
+///     ("https://example.com/two.zip", "C:\\temp\\two.zip"),
+/// ];
+/// for download in download_many(downloads, &DownloadOptions::new(), 4).await
+/// {
+///     if let Err(error) = download.result
+///     {
+///         eprintln!("{} failed: {}", download.uri, error);
+///     }
+/// }
+/// # }
+/// ```
+pub async fn download_many<I, U, P>(downloads: I, options: &DownloadOptions, concurrency: usize) -> Vec<DownloadResult>
+where
+    I: IntoIterator<Item = (U, P)>,
+    U: AsRef<str>,
+    P: AsRef<Path>,
+{
+    let downloads: Vec<(String, PathBuf)> = downloads.into_iter()
+        .map(|(uri, path)| (uri.as_ref().to_string(), path.as_ref().to_path_buf()))
+        .collect();
+
+    // Every download shares one client (and its connection pool)
+    let client = async_client(options);
+
+    futures::stream::iter(downloads)
+        .map(|(uri, path)| {
+            let client = client.as_ref();
+            async move {
+                let result = match client
+                {
+                    Ok(client) => download_to(client, &uri, options, path.clone()).await,
+                    Err(error) => Err(FileError::from_io(&path, io::Error::new(error.kind(), error.to_string()))),
+                };
+                DownloadResult { uri, path, result }
+            }
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+fn async_client(options: &DownloadOptions) -> io::Result<reqwest::Client>
+{
+    let mut builder = reqwest::Client::builder()
+        .default_headers(options.header_map()?)
+        // Matches the default of the blocking client
+        .read_timeout(options.read_timeout.unwrap_or(Duration::from_secs(30)));
+    if let Some(timeout) = options.connect_timeout
+    {
+        builder = builder.connect_timeout(timeout);
+    }
+    builder.build().map_err(|error| io::Error::other(format!("Could Not Create Client: {}", error)))
+}
+
+/// Downloads into "<path>.part" with retries, then verifies it and moves it over "path"
+async fn download_to(client: &reqwest::Client, uri: &str, options: &DownloadOptions, path: PathBuf) -> Result<(), FileError>
+{
+    let mut attempts = Attempts::new(options);
+    let downloaded = loop
+    {
+        match download_part(client, uri, options, &path, attempts.resume()).await
+        {
+            Ok(()) => break Ok(()),
+            Err(failure) => match attempts.retry_after(failure)
+            {
+                Ok(delay) => tokio::time::sleep(delay).await,
+                Err(error) => break Err(error),
+            },
+        }
+    };
+
+    // Hashing a large file would hold up the runtime
+    let options = options.clone();
+    let finish_path = path.clone();
+    tokio::task::spawn_blocking(move || finish_download(downloaded, &options, &finish_path))
+        .await
+        .map_err(|error| FileError::from_io(&path, io::Error::other(error)))?
+}
+
+/// Downloads into the ".part" file, continuing where it left off if resuming
+async fn download_part(client: &reqwest::Client, uri: &str, options: &DownloadOptions, path: &Path, resume: bool) -> Result<(), Failure>
+{
+    let part = Part::new(path, resume);
+    let mut response = client.get(uri).headers(part.request_headers()).send().await.map_err(|error| Failure::from_request(error, uri, path))?;
+
+    let mut transfer = match part.answer(options, uri, response.status(), response.headers(), response.content_length())?
+    {
+        Answer::Complete => return Ok(()),
+        Answer::Restart => return Box::pin(download_part(client, uri, options, path, false)).await,
+        Answer::Write(transfer) => transfer,
+    };
+    let mut out_file = tokio::fs::OpenOptions::new().create(true).write(true).append(transfer.append).truncate(!transfer.append).open(&part.part_path).await.map_err(Failure::Fatal)?;
+
+    // A connection dropped part way through can be picked up again
+    while let Some(chunk) = response.chunk().await.map_err(|error| Failure::from_request(error, uri, path))?
+    {
+        out_file.write_all(&chunk).await.map_err(Failure::Fatal)?;
+        transfer.written(chunk.len());
+    }
+    out_file.sync_all().await.map_err(Failure::Fatal)?;
+
+    transfer.finish(uri, path)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::{serve, TestDir};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn download_many_async() {
+
+        let body: Vec<u8> = (0..100_000u32).map(|count| (count % 239) as u8).collect();
+        let url = serve(body.clone(), 4);
+
+        let dir = TestDir::new("download_many");
+
+        // Each download gets its own result, in the order they were given
+        let downloads = vec![
+            (format!("{}/one", url), dir.join("one.bin")),
+            (format!("{}/missing", url), dir.join("missing.bin")),
+            (format!("{}/two", url), dir.join("two.bin")),
+        ];
+        let results = download_many(downloads, &DownloadOptions::new(), 2).await;
+        assert_eq!(results.len(), 3);
+        assert!(results[0].result.is_ok());
+        assert!(results[1].result.as_ref().unwrap_err().to_string().contains("404"));
+        assert!(results[2].result.is_ok());
+        assert_eq!(std::fs::read(dir.join("one.bin")).unwrap(), body);
+        assert_eq!(std::fs::read(dir.join("two.bin")).unwrap(), body);
+        assert!(!dir.join("missing.bin").exists());
+
+        // The single file version works inside the runtime as well
+        let mut file = File::new();
+        file.set_file(dir.join("three.bin").to_str().unwrap());
+        file.download_from_async(&format!("{}/three", url)).await.unwrap();
+        assert_eq!(std::fs::read(dir.join("three.bin")).unwrap(), body);
+    }
+}
@@ -0,0 +1,339 @@
+use std::io::{self, Read};
+
+use super::{File, FileError};
+
+/// How much of the start of a file is looked at to guess its encoding
+const SNIFF_SIZE: usize = 4096;
+
+/// The text encoding of a file, anything encoding_rs supports plus UTF-32 which it doesn't
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding
+{
+    Standard(&'static encoding_rs::Encoding),
+    Utf32Le,
+    Utf32Be,
+}
+
+impl Encoding
+{
+    /// Looks up an encoding by any of its WHATWG labels (eg. "utf-8", "utf-16le", "windows-1252", "shift_jis") or "utf-32le"/"utf-32be"
+    pub(crate) fn for_label(label: &str) -> Option<Self>
+    {
+        match label.trim().to_ascii_lowercase().as_str()
+        {
+            "utf-32" | "utf-32le" | "utf32" | "utf32le" => Some(Encoding::Utf32Le),
+            "utf-32be" | "utf32be" => Some(Encoding::Utf32Be),
+            _ => encoding_rs::Encoding::for_label(label.trim().as_bytes()).map(Encoding::Standard),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static str
+    {
+        match self
+        {
+            Encoding::Standard(encoding) => encoding.name(),
+            Encoding::Utf32Le => "UTF-32LE",
+            Encoding::Utf32Be => "UTF-32BE",
+        }
+    }
+
+    /// Guesses the encoding from the start of a file, first by its BOM and then by where the zero bytes are
+    pub(crate) fn sniff(sample: &[u8]) -> Self
+    {
+        if sample.starts_with(&[0xEF, 0xBB, 0xBF])
+        {
+            return Encoding::Standard(encoding_rs::UTF_8);
+        }
+        // Checked before UTF-16LE because its BOM starts the same way
+        if sample.starts_with(&[0xFF, 0xFE, 0x00, 0x00])
+        {
+            return Encoding::Utf32Le;
+        }
+        if sample.starts_with(&[0x00, 0x00, 0xFE, 0xFF])
+        {
+            return Encoding::Utf32Be;
+        }
+        if sample.starts_with(&[0xFF, 0xFE])
+        {
+            return Encoding::Standard(encoding_rs::UTF_16LE);
+        }
+        if sample.starts_with(&[0xFE, 0xFF])
+        {
+            return Encoding::Standard(encoding_rs::UTF_16BE);
+        }
+
+        // Without a BOM, mostly ASCII text in UTF-16 has a zero in every other byte
+        let pairs = sample.len() / 2;
+        if pairs >= 2
+        {
+            let even_zeros = sample.iter().step_by(2).filter(|byte| **byte == 0).count();
+            let odd_zeros = sample.iter().skip(1).step_by(2).filter(|byte| **byte == 0).count();
+            if odd_zeros * 10 > pairs * 4 && even_zeros * 10 < pairs
+            {
+                return Encoding::Standard(encoding_rs::UTF_16LE);
+            }
+            if even_zeros * 10 > pairs * 4 && odd_zeros * 10 < pairs
+            {
+                return Encoding::Standard(encoding_rs::UTF_16BE);
+            }
+        }
+
+        Encoding::Standard(encoding_rs::UTF_8)
+    }
+
+    /// The byte order mark written at the start of new files, only the UTF-16 and UTF-32 encodings get one
+    pub(crate) fn bom(&self) -> &'static [u8]
+    {
+        match self
+        {
+            Encoding::Standard(encoding) if *encoding == encoding_rs::UTF_16LE => &[0xFF, 0xFE],
+            Encoding::Standard(encoding) if *encoding == encoding_rs::UTF_16BE => &[0xFE, 0xFF],
+            Encoding::Standard(_) => &[],
+            Encoding::Utf32Le => &[0xFF, 0xFE, 0x00, 0x00],
+            Encoding::Utf32Be => &[0x00, 0x00, 0xFE, 0xFF],
+        }
+    }
+
+    /// Converts the text to bytes in this encoding (without a BOM), None if it has characters the encoding can't represent
+    pub(crate) fn encode(&self, text: &str) -> Option<Vec<u8>>
+    {
+        match self
+        {
+            // encoding_rs only decodes UTF-16, encoding it is done by hand
+            Encoding::Standard(encoding) if *encoding == encoding_rs::UTF_16LE => Some(text.encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect()),
+            Encoding::Standard(encoding) if *encoding == encoding_rs::UTF_16BE => Some(text.encode_utf16().flat_map(|unit| unit.to_be_bytes()).collect()),
+            Encoding::Standard(encoding) => {
+                // encoding_rs writes what it can't represent as "&#NNNN;", which would quietly change the text
+                let (bytes, _, unmappable) = encoding.encode(text);
+                if unmappable { None } else { Some(bytes.into_owned()) }
+            }
+            Encoding::Utf32Le => Some(text.chars().flat_map(|character| (character as u32).to_le_bytes()).collect()),
+            Encoding::Utf32Be => Some(text.chars().flat_map(|character| (character as u32).to_be_bytes()).collect()),
+        }
+    }
+
+    /// Wraps a reader of raw bytes so that it reads out UTF-8, any BOM is dropped
+    pub(crate) fn decode(self, reader: Box<dyn Read>) -> Box<dyn Read>
+    {
+        match self
+        {
+            // UTF-8 is passed through untouched so invalid bytes still show up as an error
+            Encoding::Standard(encoding) if encoding == encoding_rs::UTF_8 => Box::new(
+                encoding_rs_io::DecodeReaderBytesBuilder::new()
+                    .utf8_passthru(true)
+                    .strip_bom(true)
+                    .build(reader),
+            ),
+            Encoding::Standard(encoding) => Box::new(
+                encoding_rs_io::DecodeReaderBytesBuilder::new()
+                    .encoding(Some(encoding))
+                    .strip_bom(true)
+                    .build(reader),
+            ),
+            Encoding::Utf32Le => Box::new(Utf32Reader::new(reader, false)),
+            Encoding::Utf32Be => Box::new(Utf32Reader::new(reader, true)),
+        }
+    }
+}
+
+/// Transcodes UTF-32 to UTF-8 as it is read, invalid code points become U+FFFD
+struct Utf32Reader
+{
+    inner: io::BufReader<Box<dyn Read>>,
+    big_endian: bool,
+    at_start: bool,
+    pending: Vec<u8>,
+    position: usize,
+}
+
+impl Utf32Reader
+{
+    fn new(inner: Box<dyn Read>, big_endian: bool) -> Self
+    {
+        Self {
+            inner: io::BufReader::new(inner),
+            big_endian,
+            at_start: true,
+            pending: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Decodes the next batch of code units into "pending", returns false at the end of the stream
+    fn fill(&mut self) -> io::Result<bool>
+    {
+        let mut units = [0u8; 4 * 1024];
+        let mut filled = 0;
+        // Keep reading until there are only whole code units (or nothing more to read)
+        loop
+        {
+            let read = self.inner.read(&mut units[filled..])?;
+            filled += read;
+            if read == 0 || (filled % 4 == 0 && filled > 0)
+            {
+                break;
+            }
+        }
+        if filled == 0
+        {
+            return Ok(false);
+        }
+
+        self.pending.clear();
+        self.position = 0;
+        for unit in units[..filled].chunks(4)
+        {
+            if unit.len() < 4
+            {
+                self.pending.extend_from_slice("\u{FFFD}".as_bytes());
+                break;
+            }
+            let unit = [unit[0], unit[1], unit[2], unit[3]];
+            let value = if self.big_endian { u32::from_be_bytes(unit) } else { u32::from_le_bytes(unit) };
+            let at_start = std::mem::replace(&mut self.at_start, false);
+            if at_start && value == 0xFEFF
+            {
+                continue;
+            }
+
+            let character = char::from_u32(value).unwrap_or('\u{FFFD}');
+            let mut encoded = [0u8; 4];
+            self.pending.extend_from_slice(character.encode_utf8(&mut encoded).as_bytes());
+        }
+        Ok(true)
+    }
+}
+
+impl Read for Utf32Reader
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position >= self.pending.len()
+        {
+            if !self.fill()?
+            {
+                return Ok(0);
+            }
+        }
+
+        let available = &self.pending[self.position..];
+        let length = available.len().min(buf.len());
+        buf[..length].copy_from_slice(&available[..length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+impl File
+{
+    /// Sets the encoding used to read the file by any encoding_rs label (eg. "utf-8", "utf-16be", "windows-1252", "shift_jis")
+    /// or "utf-32le"/"utf-32be". Without this the encoding is detected from the file.
+    /// # Examples
+    /// ```
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// file.set_file("C:\\temp\\legacy.log").set_encoding("windows-1252").unwrap();
+    /// ```
+    pub fn set_encoding(&mut self, label: &str) -> Result<&mut Self, FileError>
+    {
+        let encoding = Encoding::for_label(label);
+        if encoding.is_none()
+        {
+            return Err(FileError::UnknownEncoding { path: std::path::PathBuf::from(&self.file), label: label.to_string() });
+        }
+        self.encoding = encoding;
+        Ok(self)
+    }
+
+    /// The name of the encoding the file is read with (eg. "UTF-8", "UTF-16LE"),
+    /// either the one that was set or the one detected from its BOM or contents
+    /// # Examples
+    /// ```
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// let result: Result<String, davids_awesome_library::files::FileError> = file.set_file("C:\\Windows\\WindowsUpdate.log").detect_encoding();
+    /// ```
+    pub fn detect_encoding(&mut self) -> Result<String, FileError>
+    {
+        Ok(self.read_encoding().map_err(|error| self.io_error(error))?.name().to_string())
+    }
+
+    /// The encoding to write with: the one that was set, otherwise the one the existing file is in, otherwise UTF-8
+    pub(crate) fn write_encoding(&self) -> io::Result<Encoding>
+    {
+        if self.encoding.is_none() && !std::path::Path::new(&self.file).exists()
+        {
+            return Ok(Encoding::Standard(encoding_rs::UTF_8));
+        }
+        self.read_encoding()
+    }
+
+    /// The text as bytes in the encoding, a FileError::Encoding (inside the io::Error) if the encoding can't represent all of it
+    pub(crate) fn encode_text(&self, encoding: Encoding, text: &str) -> io::Result<Vec<u8>>
+    {
+        encoding.encode(text).ok_or_else(|| {
+            FileError::Encoding { path: std::path::PathBuf::from(&self.file), encoding: encoding.name().to_string() }.into()
+        })
+    }
+
+    /// The encoding that was set, otherwise the one detected from the start of the file
+    pub(crate) fn read_encoding(&self) -> io::Result<Encoding>
+    {
+        if let Some(encoding) = self.encoding
+        {
+            return Ok(encoding);
+        }
+
+        let mut sample = Vec::with_capacity(SNIFF_SIZE);
+        self.open_raw()?.take(SNIFF_SIZE as u64).read_to_end(&mut sample)?;
+        Ok(Encoding::sniff(&sample))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::TestDir;
+
+    #[test]
+    fn detect_encodings() {
+
+        let text = "first line\nlast line \u{00e9}\u{1F600}\n";
+        let utf16le: Vec<u8> = text.encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect();
+        let utf16be: Vec<u8> = text.encode_utf16().flat_map(|unit| unit.to_be_bytes()).collect();
+        let utf32be: Vec<u8> = text.chars().flat_map(|character| (character as u32).to_be_bytes()).collect();
+
+        let cases: Vec<(&str, Vec<u8>)> = vec![
+            ("UTF-8", [&[0xEF, 0xBB, 0xBF][..], text.as_bytes()].concat()),
+            ("UTF-16LE", [&[0xFF, 0xFE][..], &utf16le].concat()),
+            ("UTF-16BE", [&[0xFE, 0xFF][..], &utf16be].concat()),
+            ("UTF-16LE", utf16le.clone()),
+            ("UTF-16BE", utf16be.clone()),
+            ("UTF-32LE", [&[0xFF, 0xFE, 0x00, 0x00][..], &text.chars().flat_map(|character| (character as u32).to_le_bytes()).collect::<Vec<u8>>()].concat()),
+            ("UTF-32BE", [&[0x00, 0x00, 0xFE, 0xFF][..], &utf32be].concat()),
+        ];
+
+        let dir = TestDir::new("encoding");
+        let path = dir.join("encoding.log");
+        for (name, bytes) in cases
+        {
+            std::fs::write(&path, bytes).unwrap();
+            let mut file = File::new();
+            file.set_file(path.to_str().unwrap());
+            assert_eq!(file.detect_encoding().unwrap(), name);
+            assert_eq!(file.first_file_line().unwrap(), "first line");
+            assert_eq!(file.last_file_line().unwrap(), "last line \u{00e9}\u{1F600}");
+        }
+
+        std::fs::write(&path, b"caf\xe9\n").unwrap();
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+        assert!(file.first_file_line().is_err());
+        assert_eq!(file.set_encoding("windows-1252").unwrap().first_file_line().unwrap(), "caf\u{00e9}");
+        assert!(file.set_encoding("not-an-encoding").is_err());
+
+        // Text the encoding can't hold is refused rather than written as "&#9731;"
+        let error = file.create_or_truncate("snowman \u{2603}\n").unwrap_err();
+        assert!(matches!(error, FileError::Encoding { ref encoding, .. } if encoding == "windows-1252"));
+        assert!(matches!(file.append_line("\u{2603}"), Err(FileError::Encoding { .. })));
+        assert_eq!(std::fs::read(&path).unwrap(), b"caf\xe9\n");
+    }
+}
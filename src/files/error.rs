@@ -0,0 +1,221 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::File;
+
+/// Why working with a file failed, always includes the path of the file involved.
+/// Converts into an io::Error (of the matching kind) so it can still be passed up with "?" from functions returning io::Result.
+/// The line reading helpers that return io::Result (first_file_line, last_file_line, find_line_by_text, time_since_last_write)
+/// carry one inside the io::Error, get it back out with "get_ref" or FileError::from_io.
+/// # Examples
+/// ```
+/// use davids_awesome_library::files::{File, FileError};
+///
+/// let mut file = File::new();
+/// match file.set_file("C:\\temp\\loggg.log").find_line_by_text("MainEngineThread is returning")
+/// {
+///     Ok(line) => println!("{}", line),
+///     Err(error) => match error.get_ref().and_then(|inner| inner.downcast_ref::<FileError>())
+///     {
+///         Some(FileError::NotFound { path, .. }) => println!("{} is missing", path.display()),
+///         Some(FileError::NoMatch { .. }) => println!("not there yet"),
+///         _ => println!("{}", error),
+///     },
+/// }
+/// ```
+#[derive(Debug)]
+pub enum FileError
+{
+    /// The file (or the directory it should be in) does not exist
+    NotFound { path: PathBuf, source: io::Error },
+    /// The file has no (non-empty) lines
+    Empty { path: PathBuf },
+    /// Nothing in the file matched the text or pattern searched for
+    NoMatch { path: PathBuf, pattern: String },
+    /// The regex pattern could not be compiled
+    InvalidPattern { path: PathBuf, pattern: String, source: regex::Error },
+    /// The encoding label given to set_encoding is not one that is known
+    UnknownEncoding { path: PathBuf, label: String },
+    /// The contents are not valid in the encoding the file is read with, or the text has characters the encoding it is written with can't represent
+    Encoding { path: PathBuf, encoding: String },
+    /// The line number is 0 or past the end of the file
+    LineOutOfRange { path: PathBuf, line: usize },
+    /// The server answered the download with an error status (eg. 404)
+    Http { path: PathBuf, url: String, status: u16 },
+    /// The download could not be made, timed out or was cut off
+    Download { path: PathBuf, url: String, source: io::Error },
+    /// The download did not match the expected checksum
+    Checksum { path: PathBuf, expected: String, actual: String },
+    /// The file is not valid CSV (or a row didn't fit the type it was read into)
+    Csv { path: PathBuf, source: csv::Error },
+    /// An entry in the archive would be extracted outside the directory it's extracted to
+    UnsafeEntry { path: PathBuf, entry: String },
+    /// Anything else the file system reported (no permission, already exists, ...)
+    Io { path: PathBuf, source: io::Error },
+}
+
+impl FileError
+{
+    /// Attaches the path to an io::Error, a FileError that was passed along inside the io::Error is taken back out
+    pub fn from_io<P: AsRef<Path>>(path: P, source: io::Error) -> Self
+    {
+        if source.get_ref().is_some_and(|inner| inner.is::<FileError>())
+        {
+            return *source.into_inner().unwrap().downcast::<FileError>().unwrap();
+        }
+
+        let path = path.as_ref().to_path_buf();
+        if source.kind() == io::ErrorKind::NotFound
+        {
+            return FileError::NotFound { path, source };
+        }
+        FileError::Io { path, source }
+    }
+
+    /// The path of the file the error happened on
+    pub fn path(&self) -> &Path
+    {
+        match self
+        {
+            FileError::NotFound { path, .. }
+            | FileError::Empty { path }
+            | FileError::NoMatch { path, .. }
+            | FileError::InvalidPattern { path, .. }
+            | FileError::UnknownEncoding { path, .. }
+            | FileError::Encoding { path, .. }
+            | FileError::LineOutOfRange { path, .. }
+            | FileError::Http { path, .. }
+            | FileError::Download { path, .. }
+            | FileError::Checksum { path, .. }
+            | FileError::Csv { path, .. }
+            | FileError::UnsafeEntry { path, .. }
+            | FileError::Io { path, .. } => path,
+        }
+    }
+
+    /// The closest io::ErrorKind, the same kind the io::Error it converts into has
+    pub fn kind(&self) -> io::ErrorKind
+    {
+        match self
+        {
+            FileError::NotFound { .. } => io::ErrorKind::NotFound,
+            FileError::Empty { .. } | FileError::NoMatch { .. } | FileError::Encoding { .. } | FileError::Checksum { .. } | FileError::Csv { .. } | FileError::UnsafeEntry { .. } => io::ErrorKind::InvalidData,
+            FileError::InvalidPattern { .. } | FileError::UnknownEncoding { .. } | FileError::LineOutOfRange { .. } => io::ErrorKind::InvalidInput,
+            FileError::Http { .. } => io::ErrorKind::Other,
+            FileError::Download { source, .. } | FileError::Io { source, .. } => source.kind(),
+        }
+    }
+}
+
+impl std::fmt::Display for FileError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            FileError::NotFound { path, .. } => write!(f, "File Not Found: {}", path.display()),
+            FileError::Empty { path } => write!(f, "File is Empty: {}", path.display()),
+            FileError::NoMatch { path, pattern } => write!(f, "String not Found in {}: {}", path.display(), pattern),
+            FileError::InvalidPattern { pattern, source, .. } => write!(f, "Regex Pattern Invalid: {}: {}", pattern, source),
+            FileError::UnknownEncoding { label, .. } => write!(f, "Unknown Encoding: {}", label),
+            FileError::Encoding { path, encoding } => write!(f, "Invalid {} in {}", encoding, path.display()),
+            FileError::LineOutOfRange { path, line } => write!(f, "Line Number Out Of Range: {} in {}", line, path.display()),
+            FileError::Http { url, status, .. } => write!(f, "HTTP Status {} for {}", status, url),
+            FileError::Download { url, source, .. } => write!(f, "Could Not Download {}: {}", url, source),
+            FileError::Checksum { path, expected, actual } => write!(f, "Checksum Mismatch for {}: expected {}, got {}", path.display(), expected, actual),
+            FileError::Csv { path, source } => write!(f, "Invalid CSV in {}: {}", path.display(), source),
+            FileError::UnsafeEntry { path, entry } => write!(f, "Unsafe Path in Archive {}: {}", path.display(), entry),
+            FileError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+        }
+    }
+}
+
+impl std::error::Error for FileError
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self
+        {
+            FileError::InvalidPattern { source, .. } => Some(source),
+            FileError::Csv { source, .. } => Some(source),
+            FileError::NotFound { source, .. } | FileError::Download { source, .. } | FileError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<FileError> for io::Error
+{
+    fn from(error: FileError) -> Self {
+        io::Error::new(error.kind(), error)
+    }
+}
+
+impl File
+{
+    /// Attaches the path of this file to an io::Error
+    pub(crate) fn io_error(&self, source: io::Error) -> FileError
+    {
+        FileError::from_io(&self.file, source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::TestDir;
+    use crate::files::SearchOptions;
+
+    #[test]
+    fn typed_errors() {
+
+        let dir = TestDir::new("errors");
+        let path = dir.join("empty.txt");
+
+        // The line helpers keep returning io::Result, the FileError is inside it
+        fn typed(error: std::io::Error) -> FileError
+        {
+            *error.into_inner().unwrap().downcast::<FileError>().unwrap()
+        }
+
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+        let error = file.first_file_line().unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+        let error = typed(error);
+        assert!(matches!(error, FileError::NotFound { .. }));
+        assert!(std::error::Error::source(&error).is_some());
+
+        std::fs::write(&path, "").unwrap();
+        assert!(matches!(typed(file.last_file_line().unwrap_err()), FileError::Empty { .. }));
+
+        std::fs::write(&path, "one\ntwo\n").unwrap();
+        match typed(file.find_line_by_text("three").unwrap_err())
+        {
+            FileError::NoMatch { path: error_path, pattern } => {
+                assert_eq!(error_path, path);
+                assert_eq!(pattern, "three");
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+
+        let error = file.find_lines("(unclosed", &SearchOptions::new()).unwrap_err();
+        assert!(matches!(error, FileError::InvalidPattern { .. }));
+        assert!(std::error::Error::source(&error).is_some());
+        assert!(matches!(file.replace_line(5, "five"), Err(FileError::LineOutOfRange { line: 5, .. })));
+        assert!(matches!(file.set_encoding("klingon"), Err(FileError::UnknownEncoding { .. })));
+
+        // Invalid UTF-8 is the same error whichever end of the file it's read from
+        let invalid = dir.join("invalid.txt");
+        std::fs::write(&invalid, b"caf\xe9\n").unwrap();
+        let mut invalid_file = File::new();
+        invalid_file.set_file(invalid.to_str().unwrap());
+        assert!(matches!(typed(invalid_file.first_file_line().unwrap_err()), FileError::Encoding { .. }));
+        assert!(matches!(typed(invalid_file.last_file_line().unwrap_err()), FileError::Encoding { .. }));
+        assert!(matches!(invalid_file.lines().unwrap().next(), Some(Err(FileError::Encoding { .. }))));
+
+        // The typed error can be taken back out of the io::Error with from_io as well
+        let error = file.find_line_by_text("three").unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(matches!(FileError::from_io(&path, error), FileError::NoMatch { .. }));
+    }
+}
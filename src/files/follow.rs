@@ -0,0 +1,102 @@
+use std::io::{self, BufRead, Seek};
+use std::path::{Path, PathBuf};
+
+/// Reads the lines appended to a file, starting over from the top when the file is replaced (eg. by log rotation) or truncated.
+/// Used by File::tail and LogReader::follow, it never waits itself.
+pub(crate) struct FollowedFile
+{
+    path: PathBuf,
+    file: Option<io::BufReader<std::fs::File>>,
+    position: u64,
+    partial: Vec<u8>,
+}
+
+impl FollowedFile
+{
+    /// Starts at the end of the file, lines already in it are skipped. A file that doesn't exist yet is picked up once it's created.
+    pub(crate) fn new<P: AsRef<Path>>(path: P) -> io::Result<Self>
+    {
+        let mut followed = FollowedFile { path: path.as_ref().to_path_buf(), file: None, position: 0, partial: Vec::new() };
+        if let Ok(length) = std::fs::metadata(&followed.path).map(|metadata| metadata.len())
+        {
+            followed.open(length)?;
+        }
+        Ok(followed)
+    }
+
+    pub(crate) fn path(&self) -> &Path
+    {
+        &self.path
+    }
+
+    /// Reads the next complete line if there is one, without its line ending.
+    /// A line that is still being written is kept until the rest of it arrives.
+    pub(crate) fn next_line(&mut self) -> io::Result<Option<String>>
+    {
+        let file = match self.file.as_mut()
+        {
+            Some(file) => file,
+            None => return Ok(None),
+        };
+        let read = file.read_until(b'\n', &mut self.partial)?;
+        self.position += read as u64;
+        if !self.partial.ends_with(b"\n")
+        {
+            return Ok(None);
+        }
+
+        let line = String::from_utf8_lossy(&self.partial).trim_end_matches(['\r', '\n']).to_string();
+        self.partial.clear();
+        Ok(Some(line))
+    }
+
+    /// Checks if the file at the path is no longer the one being read (it was replaced, or truncated).
+    /// Check it before reading what's left, so lines written just before the file was replaced are still read.
+    pub(crate) fn is_replaced(&self) -> bool
+    {
+        let file = match &self.file
+        {
+            Some(file) => file,
+            None => return self.path.exists(),
+        };
+
+        let current = std::fs::metadata(&self.path);
+        if current.is_err()
+        {
+            return false;
+        }
+        let current = current.unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            if let Ok(open) = file.get_ref().metadata()
+            {
+                if open.dev() != current.dev() || open.ino() != current.ino()
+                {
+                    return true;
+                }
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = file;
+
+        current.len() < self.position
+    }
+
+    /// Opens whatever is at the path now, from the start
+    pub(crate) fn reopen(&mut self) -> io::Result<()>
+    {
+        self.open(0)
+    }
+
+    fn open(&mut self, position: u64) -> io::Result<()>
+    {
+        let mut file = std::fs::File::open(&self.path)?;
+        file.seek(io::SeekFrom::Start(position))?;
+        self.file = Some(io::BufReader::new(file));
+        self.position = position;
+        self.partial.clear();
+        Ok(())
+    }
+}
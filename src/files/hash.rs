@@ -0,0 +1,169 @@
+use std::io::{self, Read};
+use std::path::Path;
+
+use sha2::Digest;
+
+use super::{File, FileError};
+
+/// The checksums File::hash can work out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm
+{
+    Sha256,
+    Sha1,
+    Md5,
+    Crc32,
+}
+
+enum Hasher
+{
+    Sha256(sha2::Sha256),
+    Sha1(sha1::Sha1),
+    Md5(md5::Md5),
+    Crc32(crc32fast::Hasher),
+}
+
+impl Hasher
+{
+    fn new(algorithm: HashAlgorithm) -> Self
+    {
+        match algorithm
+        {
+            HashAlgorithm::Sha256 => Hasher::Sha256(sha2::Sha256::new()),
+            HashAlgorithm::Sha1 => Hasher::Sha1(sha1::Sha1::new()),
+            HashAlgorithm::Md5 => Hasher::Md5(md5::Md5::new()),
+            HashAlgorithm::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8])
+    {
+        match self
+        {
+            Hasher::Sha256(hasher) => hasher.update(bytes),
+            Hasher::Sha1(hasher) => hasher.update(bytes),
+            Hasher::Md5(hasher) => hasher.update(bytes),
+            Hasher::Crc32(hasher) => hasher.update(bytes),
+        }
+    }
+
+    /// The checksum in lowercase hex
+    fn finish(self) -> String
+    {
+        let bytes = match self
+        {
+            Hasher::Sha256(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha1(hasher) => hasher.finalize().to_vec(),
+            Hasher::Md5(hasher) => hasher.finalize().to_vec(),
+            Hasher::Crc32(hasher) => hasher.finalize().to_be_bytes().to_vec(),
+        };
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+impl File
+{
+    /// Works out the checksum of the file (as it is on disk) in lowercase hex, reading it in chunks so large files are fine
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::files::{File, HashAlgorithm};
+    ///
+    /// let mut file = File::new();
+    /// if let Ok(checksum) = file.set_file("C:\\temp\\installer.exe").hash(HashAlgorithm::Sha256)
+    /// {
+    ///     println!("{}", checksum);
+    /// }
+    /// ```
+    pub fn hash(&self, algorithm: HashAlgorithm) -> Result<String, FileError>
+    {
+        Ok(self.hashes(&[algorithm])?.remove(0))
+    }
+
+    /// Works out several checksums while reading the file only once, in the same order as asked for
+    pub fn hashes(&self, algorithms: &[HashAlgorithm]) -> Result<Vec<String>, FileError>
+    {
+        hash_path(Path::new(&self.file), algorithms).map_err(|error| self.io_error(error))
+    }
+
+    /// Returns true if the checksum of the file matches the expected one (hex, ignoring case)
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::files::{File, HashAlgorithm};
+    ///
+    /// let mut file = File::new();
+    /// let matches = file.set_file("C:\\temp\\installer.exe").verify(HashAlgorithm::Sha1, "A94A8FE5CCB19BA61C4C0873D391E987982FBBD3");
+    /// ```
+    pub fn verify(&self, algorithm: HashAlgorithm, expected: &str) -> Result<bool, FileError>
+    {
+        Ok(self.hash(algorithm)?.eq_ignore_ascii_case(expected.trim()))
+    }
+
+    pub fn sha256(&self) -> Result<String, FileError>
+    {
+        self.hash(HashAlgorithm::Sha256)
+    }
+
+    pub fn sha1(&self) -> Result<String, FileError>
+    {
+        self.hash(HashAlgorithm::Sha1)
+    }
+
+    pub fn md5(&self) -> Result<String, FileError>
+    {
+        self.hash(HashAlgorithm::Md5)
+    }
+
+    pub fn crc32(&self) -> Result<String, FileError>
+    {
+        self.hash(HashAlgorithm::Crc32)
+    }
+}
+
+/// Streams the file at "path" through every hasher at once
+pub(crate) fn hash_path(path: &Path, algorithms: &[HashAlgorithm]) -> io::Result<Vec<String>>
+{
+    let mut hashers: Vec<Hasher> = algorithms.iter().map(|algorithm| Hasher::new(*algorithm)).collect();
+    let mut file = std::fs::File::open(path)?;
+
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop
+    {
+        let read = file.read(&mut buffer)?;
+        if read == 0
+        {
+            break;
+        }
+        for hasher in hashers.iter_mut()
+        {
+            hasher.update(&buffer[..read]);
+        }
+    }
+
+    Ok(hashers.into_iter().map(Hasher::finish).collect())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::TestDir;
+
+    #[test]
+    fn hashes() {
+
+        let dir = TestDir::new("hash");
+        let path = dir.join("hash.txt");
+        std::fs::write(&path, "test").unwrap();
+
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+
+        assert_eq!(file.sha256().unwrap(), "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08");
+        assert_eq!(file.sha1().unwrap(), "a94a8fe5ccb19ba61c4c0873d391e987982fbbd3");
+        assert_eq!(file.md5().unwrap(), "098f6bcd4621d373cade4e832627b4f6");
+        assert_eq!(file.crc32().unwrap(), "d87f7e0c");
+        assert_eq!(file.hashes(&[HashAlgorithm::Crc32, HashAlgorithm::Md5]).unwrap(), vec!["d87f7e0c", "098f6bcd4621d373cade4e832627b4f6"]);
+        assert!(file.verify(HashAlgorithm::Sha1, "A94A8FE5CCB19BA61C4C0873D391E987982FBBD3").unwrap());
+        assert!(!file.verify(HashAlgorithm::Md5, "00").unwrap());
+    }
+}
@@ -0,0 +1,426 @@
+use std::str::FromStr;
+
+use super::{File, FileError};
+
+/// An INI file, kept line by line so comments, blank lines and the order of everything survive being written back.
+/// Section and key names are matched ignoring case, the same as Windows does. Keys before the first section are in the "" section.
+/// A "; comment" after a value is not part of it (the ";" needs a space before it), quote the value to keep one in it.
+/// # Examples
+/// ```
+/// use davids_awesome_library::files::Ini;
+///
+/// let mut ini = Ini::parse("; settings\n[general]\nport = 80\n");
+/// assert_eq!(ini.get_as::<u16>("General", "PORT"), Some(80));
+///
+/// ini.set("general", "port", "8080").set("logging", "level", "debug");
+/// assert_eq!(ini.to_string(), "; settings\n[general]\nport = 8080\n\n[logging]\nlevel=debug\n");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ini
+{
+    lines: Vec<Line>,
+    line_ending: LineEnding,
+}
+
+impl Ini
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Parses INI text, lines that aren't a section, a key=value or a comment are kept as they are but otherwise ignored
+    pub fn parse(text: &str) -> Self
+    {
+        let lines = text.lines()
+            .map(|line| {
+                let trimmed = line.trim();
+                if trimmed.starts_with('[') && trimmed.ends_with(']')
+                {
+                    return Line::Section { name: trimmed[1..trimmed.len() - 1].trim().to_string(), raw: line.to_string() };
+                }
+                Line::parse_ini(line)
+            })
+            .collect();
+        Ini { lines, line_ending: LineEnding::of(text) }
+    }
+
+    /// The value of the key in the section, with any quotes around it removed
+    pub fn get(&self, section: &str, key: &str) -> Option<&str>
+    {
+        self.find(section, key).and_then(|index| self.lines[index].value())
+    }
+
+    /// The value of the key parsed into any type that implements FromStr, None if it's missing or doesn't parse
+    pub fn get_as<T: FromStr>(&self, section: &str, key: &str) -> Option<T>
+    {
+        self.get(section, key).and_then(|value| value.parse().ok())
+    }
+
+    /// Changes the value of the key in place, or adds it to the end of the section (adding the section at the end of the file if needed)
+    pub fn set(&mut self, section: &str, key: &str, value: &str) -> &mut Self
+    {
+        if let Some(index) = self.find(section, key)
+        {
+            self.lines[index].set_value(value);
+            return self;
+        }
+
+        let entry = Line::entry(key, value);
+        match self.section_end(section)
+        {
+            Some(index) => self.lines.insert(index, entry),
+            None => {
+                if self.lines.last().is_some_and(|line| !line.is_blank())
+                {
+                    self.lines.push(Line::Other(String::new()));
+                }
+                self.lines.push(Line::Section { name: section.to_string(), raw: format!("[{}]", section) });
+                self.lines.push(entry);
+            }
+        }
+        self
+    }
+
+    /// Removes the key from the section, returning the value it had
+    pub fn remove(&mut self, section: &str, key: &str) -> Option<String>
+    {
+        let index = self.find(section, key)?;
+        self.lines.remove(index).value().map(|value| value.to_string())
+    }
+
+    /// The names of the sections, in the order they are in the file
+    pub fn sections(&self) -> Vec<&str>
+    {
+        self.lines.iter()
+            .filter_map(|line| match line
+            {
+                Line::Section { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Every key and value in the section, in the order they are in the file
+    pub fn entries(&self, section: &str) -> Vec<(&str, &str)>
+    {
+        self.section_lines(section)
+            .filter_map(|index| match &self.lines[index]
+            {
+                Line::Entry { key, value, .. } => Some((key.as_str(), value.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The index of every line inside the section (not including the section header)
+    fn section_lines<'a>(&'a self, section: &'a str) -> impl Iterator<Item = usize> + 'a
+    {
+        let mut current = "";
+        self.lines.iter().enumerate().filter_map(move |(index, line)| {
+            if let Line::Section { name, .. } = line
+            {
+                current = name;
+                return None;
+            }
+            current.eq_ignore_ascii_case(section).then_some(index)
+        })
+    }
+
+    fn find(&self, section: &str, key: &str) -> Option<usize>
+    {
+        self.section_lines(section).find(|index| self.lines[*index].key().is_some_and(|name| name.eq_ignore_ascii_case(key)))
+    }
+
+    /// Where a new key in the section goes, just after its last key (or header), None if the section doesn't exist
+    fn section_end(&self, section: &str) -> Option<usize>
+    {
+        if let Some(last) = self.section_lines(section).filter(|index| self.lines[*index].key().is_some()).last()
+        {
+            return Some(last + 1);
+        }
+        if section.is_empty()
+        {
+            // Below the comments at the top of the file, they are usually about the whole file
+            let leading = self.lines.iter().take_while(|line| matches!(line, Line::Other(_))).count();
+            return Some(leading);
+        }
+        self.lines.iter()
+            .position(|line| matches!(line, Line::Section { name, .. } if name.eq_ignore_ascii_case(section)))
+            .map(|index| index + 1)
+    }
+}
+
+impl std::fmt::Display for Ini
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.line_ending.write(f, &self.lines)
+    }
+}
+
+impl File
+{
+    /// Reads the file as an INI file
+    /// # Examples
+    /// ```
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// if let Ok(ini) = file.set_file("C:\\temp\\settings.ini").read_ini()
+    /// {
+    ///     println!("{:?}", ini.get("general", "port"));
+    /// }
+    /// ```
+    pub fn read_ini(&mut self) -> Result<Ini, FileError>
+    {
+        Ok(Ini::parse(&self.read_text()?))
+    }
+
+    /// Writes the INI back to the file (in the encoding it was read with), replacing it in one go
+    /// # Examples
+    /// ```no_run
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// file.set_file("C:\\temp\\settings.ini");
+    /// let mut ini = file.read_ini().unwrap();
+    /// ini.set("general", "port", "8080");
+    /// file.write_ini(&ini).unwrap();
+    /// ```
+    pub fn write_ini(&mut self, ini: &Ini) -> Result<(), FileError>
+    {
+        self.write_atomic(&ini.to_string())
+    }
+}
+
+/// One line of an INI or key=value file, keeping the text it was read from so unchanged lines are written back exactly
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Line
+{
+    Section { name: String, raw: String },
+    /// "prefix" is everything up to the start of the value, eg. "port = ", "suffix" anything after it, eg. " ; the web port"
+    Entry { key: String, value: String, prefix: String, suffix: String, raw: String },
+    /// Blank lines, comments and anything that isn't understood
+    Other(String),
+}
+
+impl Line
+{
+    /// Parses a key=value line, "ignore" is a word before the key that isn't part of it (eg. "export" in .env files)
+    pub(super) fn parse(line: &str, ignore: &str) -> Self
+    {
+        let trimmed = line.trim_start();
+        let separator = match line.find('=')
+        {
+            Some(separator) if !trimmed.starts_with(';') && !trimmed.starts_with('#') => separator,
+            _ => return Line::Other(line.to_string()),
+        };
+
+        let mut key = line[..separator].trim();
+        if !ignore.is_empty()
+        {
+            if let Some(rest) = key.strip_prefix(ignore).filter(|rest| rest.starts_with(char::is_whitespace))
+            {
+                key = rest.trim_start();
+            }
+        }
+        if key.is_empty()
+        {
+            return Line::Other(line.to_string());
+        }
+
+        let after = &line[separator + 1..];
+        let value_start = separator + 1 + (after.len() - after.trim_start().len());
+        Line::Entry {
+            key: key.to_string(),
+            value: unquote(after.trim()).to_string(),
+            prefix: line[..value_start].to_string(),
+            suffix: String::new(),
+            raw: line.to_string(),
+        }
+    }
+
+    /// Parses a key=value line of an INI file, where a "; comment" after the value isn't part of it
+    fn parse_ini(line: &str) -> Self
+    {
+        let mut parsed = Line::parse(line, "");
+        if let Line::Entry { value, prefix, suffix, .. } = &mut parsed
+        {
+            if let Some(comment) = inline_comment(line, prefix.len())
+            {
+                let comment = comment.max(prefix.len());
+                *value = unquote(line[prefix.len()..comment].trim()).to_string();
+                *suffix = line[comment..].to_string();
+            }
+        }
+        parsed
+    }
+
+    pub(super) fn entry(key: &str, value: &str) -> Self
+    {
+        let prefix = format!("{}=", key);
+        Line::Entry { key: key.to_string(), value: value.to_string(), raw: format!("{}{}", prefix, quote(value)), prefix, suffix: String::new() }
+    }
+
+    pub(super) fn key(&self) -> Option<&str>
+    {
+        match self
+        {
+            Line::Entry { key, .. } => Some(key),
+            _ => None,
+        }
+    }
+
+    pub(super) fn value(&self) -> Option<&str>
+    {
+        match self
+        {
+            Line::Entry { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Changes the value, keeping the key, the spacing around the "=" and any comment after it as they were
+    pub(super) fn set_value(&mut self, new_value: &str)
+    {
+        if let Line::Entry { value, prefix, suffix, raw, .. } = self
+        {
+            *value = new_value.to_string();
+            // "key = ; comment" had nothing between the prefix and the comment to keep them apart
+            let gap = if suffix.is_empty() || suffix.starts_with(char::is_whitespace) { "" } else { " " };
+            *raw = format!("{}{}{}{}", prefix, quote(new_value), gap, suffix);
+        }
+    }
+
+    pub(super) fn is_blank(&self) -> bool
+    {
+        matches!(self, Line::Other(line) if line.trim().is_empty())
+    }
+
+    fn raw(&self) -> &str
+    {
+        match self
+        {
+            Line::Section { raw, .. } | Line::Entry { raw, .. } => raw,
+            Line::Other(raw) => raw,
+        }
+    }
+}
+
+/// The line endings of parsed text, so it's written back the same way
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct LineEnding
+{
+    crlf: bool,
+    trailing: bool,
+}
+
+impl Default for LineEnding
+{
+    fn default() -> Self {
+        LineEnding { crlf: false, trailing: true }
+    }
+}
+
+impl LineEnding
+{
+    pub(super) fn of(text: &str) -> Self
+    {
+        LineEnding { crlf: text.contains("\r\n"), trailing: text.ends_with('\n') || text.is_empty() }
+    }
+
+    pub(super) fn write(&self, f: &mut std::fmt::Formatter<'_>, lines: &[Line]) -> std::fmt::Result
+    {
+        let line_ending = if self.crlf { "\r\n" } else { "\n" };
+        for (index, line) in lines.iter().enumerate()
+        {
+            f.write_str(line.raw())?;
+            if self.trailing || index + 1 < lines.len()
+            {
+                f.write_str(line_ending)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Takes off one pair of matching quotes
+fn unquote(value: &str) -> &str
+{
+    for quote in ['"', '\'']
+    {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote)
+        {
+            return &value[1..value.len() - 1];
+        }
+    }
+    value
+}
+
+/// Where an inline "; comment" starts in a line (including the whitespace before it), None if there isn't one.
+/// The ";" needs whitespace before it, and one inside a quoted value doesn't count.
+fn inline_comment(line: &str, value_start: usize) -> Option<usize>
+{
+    let value = &line[value_start..];
+    let mut search_from = value_start;
+    if let Some(quote) = value.chars().next().filter(|first| *first == '"' || *first == '\'')
+    {
+        search_from = value_start + 1 + value[1..].find(quote)? + 1;
+    }
+
+    let position = line[search_from..].match_indices(';')
+        .map(|(index, _)| search_from + index)
+        .find(|index| line[..*index].ends_with(char::is_whitespace))?;
+    Some(line[..position].trim_end().len())
+}
+
+/// Quotes values that would otherwise lose spaces at either end, be read back with their quotes taken off,
+/// or have part of them read back as a comment
+fn quote(value: &str) -> String
+{
+    if value != value.trim() || unquote(value) != value || inline_comment(value, 0).is_some()
+    {
+        format!("\"{}\"", value)
+    }
+    else
+    {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::TestDir;
+
+    #[test]
+    fn keep_comments_and_order() {
+
+        let dir = TestDir::new("ini");
+
+        // INI keeps comments, ordering and line endings, and only rewrites lines that changed
+        let path = dir.join("settings.ini");
+        std::fs::write(&path, "; top\r\nversion=1\r\n[General]\r\nPort = 80\r\nname=\"  padded  \"\r\n\r\n# logging\r\n[logging]\r\nlevel=info\r\n").unwrap();
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+        let mut ini = file.read_ini().unwrap();
+        assert_eq!(ini.sections(), vec!["General", "logging"]);
+        assert_eq!(ini.get("", "version"), Some("1"));
+        assert_eq!(ini.get_as::<u16>("general", "port"), Some(80));
+        assert_eq!(ini.get("general", "name"), Some("  padded  "));
+        assert_eq!(ini.get("logging", "port"), None);
+
+        ini.set("general", "port", "8080").set("general", "enabled", "true").set("", "build", "7").set("paths", "temp", "C:\\temp");
+        assert_eq!(ini.remove("logging", "level").as_deref(), Some("info"));
+        file.write_ini(&ini).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(),
+            "; top\r\nversion=1\r\nbuild=7\r\n[General]\r\nPort = 8080\r\nname=\"  padded  \"\r\nenabled=true\r\n\r\n# logging\r\n[logging]\r\n\r\n[paths]\r\ntemp=C:\\temp\r\n");
+        assert_eq!(file.read_ini().unwrap().entries("general"), vec![("Port", "8080"), ("name", "  padded  "), ("enabled", "true")]);
+
+        // Comments after a value aren't part of it and survive a change, keys with no section go below the comments on top
+        let mut ini = Ini::parse("; settings\n\n[general]\nport = 80 ; the web port\nempty = ; nothing yet\nquoted = \"a ; b\" ; kept\n");
+        assert_eq!(ini.get("general", "port"), Some("80"));
+        assert_eq!(ini.get("general", "empty"), Some(""));
+        assert_eq!(ini.get("general", "quoted"), Some("a ; b"));
+        ini.set("general", "port", "8080").set("general", "empty", "x ;y").set("", "version", "2");
+        assert_eq!(ini.to_string(), "; settings\n\nversion=2\n[general]\nport = 8080 ; the web port\nempty = \"x ;y\" ; nothing yet\nquoted = \"a ; b\" ; kept\n");
+        assert_eq!(Ini::parse(&ini.to_string()).get("general", "empty"), Some("x ;y"));
+    }
+}
@@ -0,0 +1,139 @@
+use std::str::FromStr;
+
+use super::ini::{Line, LineEnding};
+use super::{File, FileError};
+
+/// A simple "key=value" file (.env, .properties, ...), kept line by line so comments and ordering survive being written back.
+/// Keys are matched exactly, and an "export " in front of a key is ignored.
+/// # Examples
+/// ```
+/// use davids_awesome_library::files::KeyValues;
+///
+/// let mut values = KeyValues::parse("# database\nexport HOST=localhost\nPORT=5432\n");
+/// assert_eq!(values.get("HOST"), Some("localhost"));
+/// assert_eq!(values.get_as::<u16>("PORT"), Some(5432));
+///
+/// values.set("PORT", "5433").set("USER", "admin");
+/// assert_eq!(values.to_string(), "# database\nexport HOST=localhost\nPORT=5433\nUSER=admin\n");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyValues
+{
+    lines: Vec<Line>,
+    line_ending: LineEnding,
+}
+
+impl KeyValues
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Parses key=value text, lines without an "=" and comments ("#" or ";") are kept as they are but otherwise ignored
+    pub fn parse(text: &str) -> Self
+    {
+        KeyValues { lines: text.lines().map(|line| Line::parse(line, "export")).collect(), line_ending: LineEnding::of(text) }
+    }
+
+    /// The value of the key, with any quotes around it removed. If a key is in the file more than once the last one wins.
+    pub fn get(&self, key: &str) -> Option<&str>
+    {
+        self.find(key).and_then(|index| self.lines[index].value())
+    }
+
+    /// The value of the key parsed into any type that implements FromStr, None if it's missing or doesn't parse
+    pub fn get_as<T: FromStr>(&self, key: &str) -> Option<T>
+    {
+        self.get(key).and_then(|value| value.parse().ok())
+    }
+
+    /// Changes the value of the key in place, or adds it to the end
+    pub fn set(&mut self, key: &str, value: &str) -> &mut Self
+    {
+        match self.find(key)
+        {
+            Some(index) => self.lines[index].set_value(value),
+            None => self.lines.push(Line::entry(key, value)),
+        }
+        self
+    }
+
+    /// Removes the key, returning the value it had
+    pub fn remove(&mut self, key: &str) -> Option<String>
+    {
+        let mut removed = None;
+        while let Some(index) = self.find(key)
+        {
+            removed = removed.or_else(|| self.lines[index].value().map(|value| value.to_string()));
+            self.lines.remove(index);
+        }
+        removed
+    }
+
+    /// Every key and value, in the order they are in the file
+    pub fn entries(&self) -> Vec<(&str, &str)>
+    {
+        self.lines.iter().filter_map(|line| Some((line.key()?, line.value()?))).collect()
+    }
+
+    fn find(&self, key: &str) -> Option<usize>
+    {
+        self.lines.iter().rposition(|line| line.key() == Some(key))
+    }
+}
+
+impl std::fmt::Display for KeyValues
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.line_ending.write(f, &self.lines)
+    }
+}
+
+impl File
+{
+    /// Reads the file as key=value lines
+    /// # Examples
+    /// ```
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// if let Ok(values) = file.set_file("C:\\temp\\.env").read_key_values()
+    /// {
+    ///     println!("{:?}", values.get("HOST"));
+    /// }
+    /// ```
+    pub fn read_key_values(&mut self) -> Result<KeyValues, FileError>
+    {
+        Ok(KeyValues::parse(&self.read_text()?))
+    }
+
+    /// Writes the key=value lines back to the file (in the encoding it was read with), replacing it in one go
+    pub fn write_key_values(&mut self, values: &KeyValues) -> Result<(), FileError>
+    {
+        self.write_atomic(&values.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::TestDir;
+
+    #[test]
+    fn keep_comments_and_order() {
+
+        let dir = TestDir::new("key_values");
+        let path = dir.join(".env");
+        std::fs::write(&path, "# db\nexport HOST=localhost\nPORT='5432'\nnot a setting").unwrap();
+
+        // Comments and lines that aren't a setting are kept, "export" is kept on the key it was on
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+        let mut values = file.read_key_values().unwrap();
+        assert_eq!(values.entries(), vec![("HOST", "localhost"), ("PORT", "5432")]);
+        values.set("HOST", "db.local").set("USER", "admin");
+        assert_eq!(values.remove("PORT").as_deref(), Some("5432"));
+        file.write_key_values(&values).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "# db\nexport HOST=db.local\nnot a setting\nUSER=admin");
+    }
+}
@@ -0,0 +1,138 @@
+use chrono::{DateTime, Local};
+
+use super::{File, FileError};
+
+/// What the file system knows about a file, see File::metadata
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metadata
+{
+    /// Size in bytes
+    pub size: u64,
+    /// None where the file system doesn't keep creation times
+    pub created: Option<DateTime<Local>>,
+    pub modified: Option<DateTime<Local>>,
+    pub accessed: Option<DateTime<Local>>,
+    pub read_only: bool,
+    /// The permission bits (eg. 0o644), only available on unix
+    pub mode: Option<u32>,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+}
+
+impl File
+{
+    /// Reads the size, times and permissions of the file. Symlinks are followed, is_symlink tells if the path itself is one.
+    /// # Examples
+    /// ```
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// if let Ok(metadata) = file.set_file("C:\\Windows\\WindowsUpdate.log").metadata()
+    /// {
+    ///     println!("{} bytes, last written {:?}", metadata.size, metadata.modified);
+    /// }
+    /// ```
+    pub fn metadata(&self) -> Result<Metadata, FileError>
+    {
+        let metadata = std::fs::metadata(&self.file).map_err(|error| self.io_error(error))?;
+        let is_symlink = std::fs::symlink_metadata(&self.file).map(|link| link.file_type().is_symlink()).unwrap_or(false);
+
+        Ok(Metadata {
+            size: metadata.len(),
+            created: metadata.created().ok().map(DateTime::from),
+            modified: metadata.modified().ok().map(DateTime::from),
+            accessed: metadata.accessed().ok().map(DateTime::from),
+            read_only: metadata.permissions().readonly(),
+            mode: mode(&metadata),
+            is_dir: metadata.is_dir(),
+            is_symlink,
+        })
+    }
+
+    /// The size of the file in bytes
+    pub fn size(&self) -> Result<u64, FileError>
+    {
+        std::fs::metadata(&self.file).map(|metadata| metadata.len()).map_err(|error| self.io_error(error))
+    }
+
+    /// When the file was last written to
+    pub fn modified(&self) -> Result<DateTime<Local>, FileError>
+    {
+        std::fs::metadata(&self.file).and_then(|metadata| metadata.modified()).map(DateTime::from).map_err(|error| self.io_error(error))
+    }
+
+    /// Marks the file as read-only, or writable again.
+    /// On unix this only changes the write bits, the same as "chmod a-w" / "chmod u+w".
+    pub fn set_read_only(&mut self, read_only: bool) -> Result<(), FileError>
+    {
+        let mut permissions = std::fs::metadata(&self.file).map_err(|error| self.io_error(error))?.permissions();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = if read_only { permissions.mode() & !0o222 } else { permissions.mode() | 0o200 };
+            permissions.set_mode(mode);
+        }
+        #[cfg(not(unix))]
+        permissions.set_readonly(read_only);
+        std::fs::set_permissions(&self.file, permissions).map_err(|error| self.io_error(error))
+    }
+
+    /// Sets the permission bits of the file (eg. 0o755), fails with Unsupported where there are none
+    pub fn set_mode(&mut self, mode: u32) -> Result<(), FileError>
+    {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&self.file, std::fs::Permissions::from_mode(mode)).map_err(|error| self.io_error(error))
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = mode;
+            Err(self.io_error(std::io::Error::new(std::io::ErrorKind::Unsupported, "Permission Bits Not Supported")))
+        }
+    }
+}
+
+#[cfg(unix)]
+fn mode(metadata: &std::fs::Metadata) -> Option<u32>
+{
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn mode(_metadata: &std::fs::Metadata) -> Option<u32>
+{
+    None
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::TestDir;
+
+    #[test]
+    fn metadata_and_read_only() {
+
+        let dir = TestDir::new("metadata");
+        let path = dir.join("metadata.txt");
+        std::fs::write(&path, "test").unwrap();
+
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+
+        let metadata = file.metadata().unwrap();
+        assert_eq!(metadata.size, 4);
+        assert!(!metadata.is_dir && !metadata.read_only);
+        assert!(metadata.modified.unwrap() <= chrono::Local::now());
+
+        file.set_read_only(true).unwrap();
+        assert!(file.metadata().unwrap().read_only);
+        file.set_read_only(false).unwrap();
+        assert!(!file.metadata().unwrap().read_only);
+
+        // A modification time in the future is not a panic
+        let future = std::time::SystemTime::now() + std::time::Duration::from_secs(3600);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(future).unwrap();
+        assert_eq!(file.time_since_last_write().unwrap(), 0);
+    }
+}
@@ -1,21 +1,59 @@
+mod encoding;
+mod error;
 mod read;
 mod download;
+#[cfg(feature = "async")]
+mod download_async;
 mod remove;
+mod search;
 mod create;
+mod copy;
+mod archive;
 mod modify;
+mod metadata;
+mod hash;
+mod watch;
+mod follow;
+mod ini;
+mod key_value;
+mod delimited;
 pub mod size;
-/// Used for interating with files
-/// Use .new() then .set_file("") to set the file used to read/write  
-/// 
+#[cfg(test)]
+mod test_support;
 
+pub use archive::{ArchiveEntry, ArchiveFormat};
+pub use copy::{CopyAction, CopyOptions, CopyProgress, CopyReport, Overwrite, Verify};
+pub use delimited::{CsvOptions, CsvTable};
+pub use download::{DownloadOptions, Progress};
+pub use error::FileError;
+#[cfg(feature = "async")]
+pub use download_async::{download_many, DownloadResult};
+pub use hash::HashAlgorithm;
+pub use ini::Ini;
+pub use key_value::KeyValues;
+pub use metadata::Metadata;
+pub use read::Lines;
+pub use remove::{RemoveAction, RemoveOptions, RemoveReport};
+pub use search::{LineMatch, SearchOptions};
+pub use watch::{Tail, WatchEvent, WatchOptions, Watcher};
+pub(crate) use follow::FollowedFile;
 
+/// Used for interating with files
+/// Use .new() then .set_file("") to set the file used to read/write  
 pub struct File
 {
     file: String,
-    is_utf16le: bool
+    encoding: Option<encoding::Encoding>,
 }
 
 
+impl Default for File
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl File
 {
 
@@ -24,7 +62,7 @@ impl File
     {
         Self { 
             file: String::new(), 
-            is_utf16le: false 
+            encoding: None,
         }
     }
 
@@ -40,12 +78,11 @@ impl File
         self
     }
 
-    /// Setting this currently only helps with attempting to read a file.
-    /// If you attempt to read a UTF-16LE file without setting this, you will receive an error.
+    /// Reads the file as UTF-16LE, the same as set_encoding("utf-16le").
+    /// Only needed when the file has no BOM and the encoding can't be detected from its contents.
     pub fn is_utf16le(&mut self) -> &mut Self
     {
-        self.is_utf16le = true;
+        self.encoding = Some(encoding::Encoding::Standard(encoding_rs::UTF_16LE));
         self
     }
 }
-
@@ -0,0 +1,244 @@
+use std::io::{self, Read, Write};
+
+use super::{File, FileError};
+
+impl File
+{
+    /// Adds a line to the end of the file, creating it if needed.
+    /// The line is written in the encoding of the existing file (or the one set with "set_encoding").
+    /// # Examples
+    /// ```no_run
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// let result = file.set_file("C:\\temp\\loggg.log").append_line("another line");
+    /// ```
+    pub fn append_line(&mut self, line: &str) -> Result<(), FileError>
+    {
+        self.append_bytes(line).map_err(|error| self.io_error(error))
+    }
+
+    fn append_bytes(&mut self, line: &str) -> io::Result<()>
+    {
+        let is_empty = std::fs::metadata(&self.file).map(|metadata| metadata.len() == 0).unwrap_or(true);
+        if is_empty
+        {
+            return Ok(self.create_or_truncate(&format!("{}\n", line))?);
+        }
+
+        let encoding = self.write_encoding()?;
+        let windows_ending = self.encode_text(encoding, "\r\n")?;
+        let line_ending = if self.ends_with(&windows_ending)? { windows_ending } else { self.encode_text(encoding, "\n")? };
+
+        // Make sure the line starts on its own line, even if the file doesn't end with a line ending
+        let mut bytes = Vec::new();
+        if !self.ends_with(&line_ending)?
+        {
+            bytes.extend_from_slice(&line_ending);
+        }
+        bytes.extend(self.encode_text(encoding, line)?);
+        bytes.extend_from_slice(&line_ending);
+
+        let mut out_file = std::fs::OpenOptions::new().append(true).open(&self.file)?;
+        out_file.write_all(&bytes)?;
+        out_file.flush()
+    }
+
+    /// Inserts a line so that it ends up at the line number (starting at 1), a line number one past the end appends it
+    /// # Examples
+    /// ```no_run
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// let result = file.set_file("C:\\temp\\loggg.log").insert_line(1, "a new first line");
+    /// ```
+    pub fn insert_line(&mut self, line_number: usize, text: &str) -> Result<(), FileError>
+    {
+        let mut contents = self.read_contents()?;
+        if line_number == 0 || line_number > contents.lines.len() + 1
+        {
+            return Err(FileError::LineOutOfRange { path: std::path::PathBuf::from(&self.file), line: line_number });
+        }
+
+        contents.lines.insert(line_number - 1, text.to_string());
+        self.write_contents(&contents)
+    }
+
+    /// Replaces the line at the line number (starting at 1)
+    /// # Examples
+    /// ```no_run
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// let result = file.set_file("C:\\temp\\loggg.log").replace_line(3, "the new third line");
+    /// ```
+    pub fn replace_line(&mut self, line_number: usize, text: &str) -> Result<(), FileError>
+    {
+        let mut contents = self.read_contents()?;
+        if line_number == 0 || line_number > contents.lines.len()
+        {
+            return Err(FileError::LineOutOfRange { path: std::path::PathBuf::from(&self.file), line: line_number });
+        }
+
+        contents.lines[line_number - 1] = text.to_string();
+        self.write_contents(&contents)
+    }
+
+    /// Replaces every match of the regex pattern in every line, "$1"/"${name}" in the replacement are filled in with the capture groups.
+    /// Returns the number of lines that were changed, the file is only rewritten if there was at least one.
+    /// # Examples
+    /// ```no_run
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// let result: Result<usize, davids_awesome_library::files::FileError> = file.set_file("C:\\temp\\settings.ini").replace_matching(r"^port=\d+$", "port=8080");
+    /// ```
+    pub fn replace_matching(&mut self, pattern: &str, replacement: &str) -> Result<usize, FileError>
+    {
+        let regex = self.build_regex(pattern)?;
+        let mut contents = self.read_contents()?;
+
+        let mut changed = 0;
+        for line in contents.lines.iter_mut()
+        {
+            if regex.is_match(line)
+            {
+                *line = regex.replace_all(line, replacement).to_string();
+                changed += 1;
+            }
+        }
+
+        if changed > 0
+        {
+            self.write_contents(&contents)?;
+        }
+        Ok(changed)
+    }
+
+    /// Inserts a line after every line matching the regex pattern, returns how many were inserted
+    /// # Examples
+    /// ```no_run
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// let result: Result<usize, davids_awesome_library::files::FileError> = file.set_file("C:\\temp\\settings.ini").insert_after_matching(r"^\[general\]$", "enabled=true");
+    /// ```
+    pub fn insert_after_matching(&mut self, pattern: &str, text: &str) -> Result<usize, FileError>
+    {
+        let regex = self.build_regex(pattern)?;
+        let mut contents = self.read_contents()?;
+
+        let mut lines = Vec::with_capacity(contents.lines.len());
+        let mut inserted = 0;
+        for line in contents.lines
+        {
+            let is_match = regex.is_match(&line);
+            lines.push(line);
+            if is_match
+            {
+                lines.push(text.to_string());
+                inserted += 1;
+            }
+        }
+        contents.lines = lines;
+
+        if inserted > 0
+        {
+            self.write_contents(&contents)?;
+        }
+        Ok(inserted)
+    }
+
+    fn build_regex(&self, pattern: &str) -> Result<regex::Regex, FileError>
+    {
+        regex::Regex::new(pattern).map_err(|source| FileError::InvalidPattern { path: std::path::PathBuf::from(&self.file), pattern: pattern.to_string(), source })
+    }
+
+    /// Reads and decodes the whole file
+    pub(crate) fn read_text(&self) -> Result<String, FileError>
+    {
+        let mut text = String::new();
+        self.read_encoding()
+            .and_then(|encoding| encoding.decode(self.open_raw()?).read_to_string(&mut text))
+            .map_err(|error| self.io_error(error))?;
+        Ok(text)
+    }
+
+    /// Reads the whole file as lines, remembering its line endings so it can be written back the same way
+    fn read_contents(&mut self) -> Result<Contents, FileError>
+    {
+        let text = self.read_text()?;
+        let line_ending = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        Ok(Contents {
+            lines: text.lines().map(|line| line.to_string()).collect(),
+            line_ending,
+            trailing_line_ending: text.ends_with('\n') || text.is_empty(),
+        })
+    }
+
+    fn write_contents(&mut self, contents: &Contents) -> Result<(), FileError>
+    {
+        let mut text = contents.lines.join(contents.line_ending);
+        if contents.trailing_line_ending && !contents.lines.is_empty()
+        {
+            text.push_str(contents.line_ending);
+        }
+        self.write_atomic(&text)
+    }
+
+    /// Checks the raw bytes at the end of the file
+    fn ends_with(&self, bytes: &[u8]) -> io::Result<bool>
+    {
+        use std::io::Seek;
+
+        let mut file = std::fs::File::open(&self.file)?;
+        let length = file.metadata()?.len();
+        if length < bytes.len() as u64
+        {
+            return Ok(false);
+        }
+
+        let mut end = vec![0; bytes.len()];
+        file.seek(io::SeekFrom::End(-(bytes.len() as i64)))?;
+        file.read_exact(&mut end)?;
+        Ok(end == bytes)
+    }
+}
+
+/// A file split up into lines by read_contents
+struct Contents
+{
+    lines: Vec<String>,
+    line_ending: &'static str,
+    trailing_line_ending: bool,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::TestDir;
+
+    #[test]
+    fn create_and_modify() {
+
+        let dir = TestDir::new("modify");
+        let path = dir.join("modify.txt");
+
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+        file.create_new("[general]\r\nport=80\r\n").unwrap();
+        assert!(file.create_new("again").is_err());
+
+        file.append_line("name=test").unwrap();
+        file.insert_line(1, "; settings").unwrap();
+        file.replace_line(3, "port=8080").unwrap();
+        assert_eq!(file.insert_after_matching(r"^\[general\]$", "enabled=true").unwrap(), 1);
+        assert_eq!(file.replace_matching(r"^name=(\w+)$", "name=${1}_renamed").unwrap(), 1);
+        assert!(file.replace_line(10, "missing").is_err());
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "; settings\r\n[general]\r\nenabled=true\r\nport=8080\r\nname=test_renamed\r\n");
+
+        // UTF-16LE files get a BOM, and keep their encoding when they are changed later
+        file.is_utf16le().create_or_truncate("first\n").unwrap();
+        let mut reopened = File::new();
+        reopened.set_file(path.to_str().unwrap());
+        reopened.append_line("second \u{00e9}").unwrap();
+        reopened.replace_line(1, "changed").unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let expected: Vec<u8> = "changed\nsecond \u{00e9}\n".encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect();
+        assert_eq!(bytes, [&[0xFF, 0xFE][..], &expected].concat());
+        assert_eq!(reopened.last_file_line().unwrap(), "second \u{00e9}");
+    }
+}
@@ -1,9 +1,60 @@
-use std::io::{self, Read};
-use super::File;
+use std::io::{self, BufRead, Read, Seek};
+use std::path::PathBuf;
 
-impl File
+use super::encoding::Encoding;
+use super::{File, FileError};
+
+/// How much of the file is read at a time when searching backwards from the end of it
+const CHUNK_SIZE: u64 = 64 * 1024;
+
+/// Iterates over the lines of a file one at a time (without the line endings), created by File::lines
+pub struct Lines
 {
+    reader: Box<dyn BufRead>,
+    path: PathBuf,
+}
+
+impl Iterator for Lines
+{
+    type Item = Result<String, FileError>;
+
+    /// A line that isn't valid UTF-8 is a FileError::Encoding, the same as the one inside the io::Error from File::last_file_line.
+    /// Other encodings are converted to UTF-8 as they are read, so only UTF-8 files can have one.
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = Vec::new();
+        match self.reader.read_until(b'\n', &mut line)
+        {
+            Ok(0) => None,
+            Ok(_) => {
+                let length = line.len() - line.iter().rev().take_while(|byte| **byte == b'\r' || **byte == b'\n').count();
+                line.truncate(length);
+                Some(String::from_utf8(line).map_err(|_| FileError::Encoding { path: self.path.clone(), encoding: "UTF-8".to_string() }))
+            }
+            Err(error) => Some(Err(FileError::from_io(&self.path, error))),
+        }
+    }
+}
 
+impl File
+{
+    /// Streams the file line by line instead of loading all of it into memory
+    /// # Examples
+    /// ```
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// if let Ok(lines) = file.set_file("C:\\Windows\\WindowsUpdate.log").lines()
+    /// {
+    ///     for line in lines.flatten()
+    ///     {
+    ///         println!("{}", line);
+    ///     }
+    /// }
+    /// ```
+    pub fn lines(&mut self) -> Result<Lines, FileError>
+    {
+        let encoding = self.read_encoding().map_err(|error| self.io_error(error))?;
+        let decoded = encoding.decode(self.open_raw().map_err(|error| self.io_error(error))?);
+        Ok(Lines { reader: Box::new(io::BufReader::new(decoded)), path: PathBuf::from(&self.file) })
+    }
 
     /// Set the file that will be analyzed/read, then gets the last (non-empty) line in the file 
     /// # Examples
@@ -13,21 +64,30 @@ impl File
     /// ```
     pub fn last_file_line(&mut self) -> io::Result<String>
     {
-        let file = self.file_to_string()?;
+        let is_utf8 = self.read_encoding().map_err(|error| self.io_error(error))? == Encoding::Standard(encoding_rs::UTF_8);
+        let last_line = if !is_utf8 || self.is_gzip()
+        {
+            // Can't seek into the middle of these, so stream through and keep the last line seen
+            let mut last_line = None;
+            for line in self.lines()?
+            {
+                let line = line?;
+                if !line.trim().is_empty()
+                {
+                    last_line = Some(line.trim().to_string());
+                }
+            }
+            last_line
+        } else {
+            self.last_line_backwards().map_err(|error| self.io_error(error))?
+        };
 
-        let last_line = file.split("\n")
-        .map(|part| part.trim())
-        .filter(|part| !part.is_empty())
-        .last();
-        
         if last_line.is_none()
         {
-            return Err(io::Error::new(io::ErrorKind::InvalidData, "File is Empty"));
+            return Err(FileError::Empty { path: PathBuf::from(&self.file) }.into());
         }
         
-        let last_line = last_line.unwrap().to_string();
-
-        Ok(last_line)
+        Ok(last_line.unwrap())
     }
 
     /// Set the file that will be analyzed/read, then gets the first (non-empty) line in the file 
@@ -36,24 +96,18 @@ impl File
     /// let mut file = davids_awesome_library::files::File::new();
     /// let result: Result<String, std::io::Error> = file.set_file("C:\\Windows\\WindowsUpdate.log").first_file_line();
     /// ```
-    
     pub fn first_file_line(&mut self) -> io::Result<String>
     {
-        let file = self.file_to_string()?;
-
-        let first_line = file.split("\n")
-        .map(|part| part.trim())
-        .filter(|part| !part.is_empty())
-        .next();
-        
-        if first_line.is_none()
+        for line in self.lines()?
         {
-            return Err(io::Error::new(io::ErrorKind::InvalidData, "File is Empty"));
+            let line = line?;
+            if !line.trim().is_empty()
+            {
+                return Ok(line.trim().to_string());
+            }
         }
-        
-        let first_line = first_line.unwrap().to_string();
 
-        Ok(first_line)
+        Err(FileError::Empty { path: PathBuf::from(&self.file) }.into())
     }
 
     /// Set the file that will be analyzed/read, then gets the first (non-empty) line in the file 
@@ -62,68 +116,126 @@ impl File
     /// let mut file = davids_awesome_library::files::File::new();
     /// let result: Result<String, std::io::Error> = file.set_file("C:\\temp\\loggg.log").find_line_by_text("MainEngineThread is returning");
     /// ```
-    
     pub fn find_line_by_text(&mut self, text: &str) -> io::Result<String>
     {
-        let file = self.file_to_string()?;
-
-
-        let first_line = file.split("\n")
-        .map(|part| part.trim())
-        .filter(|part| !part.is_empty());
-        
-        for line in first_line
+        for line in self.lines()?
         {
-            if line.contains(text)
+            let line = line?;
+            if !line.trim().is_empty() && line.contains(text)
             {
-                return Ok(line.to_string());
+                return Ok(line.trim().to_string());
             }
         }
 
-        return Err(io::Error::new(io::ErrorKind::InvalidData, "String not Found"));
+        Err(FileError::NoMatch { path: PathBuf::from(&self.file), pattern: text.to_string() }.into())
     }
 
-    /// Reads the metadata to determine the amount of time that has passed between now and the last write in seconds
+    /// Reads the metadata to determine the amount of time that has passed between now and the last write in seconds.
+    /// A last write time in the future (eg. after a clock change) counts as 0.
     pub fn time_since_last_write(&mut self) -> io::Result<u64>
     {
-        let file = std::fs::File::open(&self.file)?;
-        let last_modified_date = std::fs::File::metadata(&file)?
-        .modified()?
-        .elapsed().unwrap().as_secs();
-
-        return Ok(last_modified_date);
-
+        let last_modified_date = std::fs::metadata(&self.file).and_then(|metadata| metadata.modified()).map_err(|error| self.io_error(error))?;
+        Ok(last_modified_date.elapsed().map(|elapsed| elapsed.as_secs()).unwrap_or(0))
+    }
 
+    /// Opens the file for reading, transparently decompressing it when the path ends in ".gz"
+    pub(crate) fn open_raw(&self) -> io::Result<Box<dyn Read>>
+    {
+        let file = std::fs::File::open(&self.file)?;
+        if self.is_gzip()
+        {
+            return Ok(Box::new(flate2::read::MultiGzDecoder::new(file)));
+        }
+        Ok(Box::new(file))
     }
 
+    fn is_gzip(&self) -> bool
+    {
+        std::path::Path::new(&self.file).extension().is_some_and(|extension| extension == "gz")
+    }
 
-    /// Converts a file to a string so it can be read.
-    fn file_to_string(&mut self) -> io::Result<String>
+    /// Reads the file backwards from the end in chunks until a full non-empty line is found
+    fn last_line_backwards(&self) -> io::Result<Option<String>>
     {
-        if self.is_utf16le
+        let mut file = std::fs::File::open(&self.file)?;
+        let mut end = file.metadata()?.len();
+
+        // Bytes from the start of the current chunk up to the earliest line that was already checked
+        let mut tail: Vec<u8> = Vec::new();
+        while end > 0
         {
+            let start = end.saturating_sub(CHUNK_SIZE);
+            let mut chunk = vec![0; (end - start) as usize];
+            file.seek(io::SeekFrom::Start(start))?;
+            file.read_exact(&mut chunk)?;
+            chunk.extend_from_slice(&tail);
+            end = start;
+
+            let mut lines: Vec<&[u8]> = chunk.split(|byte| *byte == b'\n').collect();
+            // The first piece might be cut off part way through a line unless this is the start of the file
+            let first = if start > 0 { lines.remove(0) } else { &[] };
+
+            for line in lines.iter().rev()
+            {
+                let line = std::str::from_utf8(line)
+                    .map_err(|_| FileError::Encoding { path: PathBuf::from(&self.file), encoding: "UTF-8".to_string() })?
+                    .trim_start_matches('\u{FEFF}');
+                if !line.trim().is_empty()
+                {
+                    return Ok(Some(line.trim().to_string()));
+                }
+            }
 
-            let mut raw_file = std::fs::File::open(&self.file)?;
-            let mut buffer = Vec::new();
-            raw_file.read_to_end(&mut buffer).unwrap();
-            let file = Self::decode_utf16le(buffer);
-            return Ok(file);
+            tail = first.to_vec();
         }
-        
-        std::fs::read_to_string(&self.file)
-        
+
+        Ok(None)
     }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::TestDir;
 
-    /// Because Rust assumes a file is UTF-8, extra work needs to be done to convert UTF-16LE to a String
-    fn decode_utf16le(buf: Vec<u8>) -> String {
-        let enc = encoding_rs::Encoding::for_label("utf-16le".as_bytes());
-        let mut dec = encoding_rs_io::DecodeReaderBytesBuilder::new()
-            .encoding(enc)
-            .build(&buf[..]);
-        let mut res = String::new();
-        dec.read_to_string(&mut res).unwrap();
-        res
+    #[test]
+    fn read_gzip_file() {
+
+        use std::io::Write;
+
+        let dir = TestDir::new("read");
+        let path = dir.join("read.log.gz");
+        let mut encoder = flate2::write::GzEncoder::new(std::fs::File::create(&path).unwrap(), flate2::Compression::default());
+        encoder.write_all(b"\nfirst line\nsecond line\nlast line\n\n").unwrap();
+        encoder.finish().unwrap();
+
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+        assert_eq!(file.first_file_line().unwrap(), "first line");
+        assert_eq!(file.last_file_line().unwrap(), "last line");
+        assert_eq!(file.find_line_by_text("second").unwrap(), "second line");
     }
 
+    #[test]
+    fn last_line_across_chunks() {
+
+        use std::io::Write;
 
-}
\ No newline at end of file
+        let dir = TestDir::new("chunks");
+        let path = dir.join("chunks.log");
+        let mut out = std::fs::File::create(&path).unwrap();
+        for count in 0..20_000
+        {
+            write!(out, "line number {}\r\n", count).unwrap();
+        }
+        write!(out, "{}\r\n  \n\n", "x".repeat(100_000)).unwrap();
+        drop(out);
+
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+        assert_eq!(file.last_file_line().unwrap(), "x".repeat(100_000));
+        assert_eq!(file.first_file_line().unwrap(), "line number 0");
+        assert_eq!(file.lines().unwrap().count(), 20_003);
+    }
+}
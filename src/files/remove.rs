@@ -0,0 +1,281 @@
+use std::io::{self, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use super::{CopyOptions, File, FileError};
+
+/// Settings for File::remove
+#[derive(Debug, Clone, Default)]
+pub struct RemoveOptions
+{
+    trash_dir: Option<PathBuf>,
+    overwrite_passes: u32,
+    dry_run: bool,
+}
+
+impl RemoveOptions
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Move the file into this directory instead of deleting it, the directory is created if needed
+    pub fn trash_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self
+    {
+        self.trash_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Overwrite the contents of the file this many times before deleting it.
+    /// A symlink is only unlinked, the file it points to is left alone.
+    pub fn secure_overwrite(&mut self, passes: u32) -> &mut Self
+    {
+        self.overwrite_passes = passes;
+        self
+    }
+
+    /// Only report what would be done, nothing is changed
+    pub fn dry_run(&mut self, enabled: bool) -> &mut Self
+    {
+        self.dry_run = enabled;
+        self
+    }
+}
+
+/// What File::remove did (or would do on a dry run)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoveAction
+{
+    /// The file was deleted
+    Deleted,
+    /// The file was overwritten this many times and then deleted
+    Overwritten(u32),
+    /// The file was moved to this path
+    MovedToTrash(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoveReport
+{
+    pub path: PathBuf,
+    pub size: u64,
+    pub action: RemoveAction,
+    pub dry_run: bool,
+}
+
+impl std::fmt::Display for RemoveReport
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (action, dry_run_action) = match &self.action
+        {
+            RemoveAction::Deleted => ("Deleted", "Would delete"),
+            RemoveAction::Overwritten(_) => ("Overwrote and deleted", "Would overwrite and delete"),
+            RemoveAction::MovedToTrash(_) => ("Moved", "Would move"),
+        };
+        let action = if self.dry_run { dry_run_action } else { action };
+        write!(f, "{} {} ({} bytes)", action, self.path.display(), self.size)?;
+
+        match &self.action
+        {
+            RemoveAction::Overwritten(passes) => write!(f, " after {} passes", passes),
+            RemoveAction::MovedToTrash(to) => write!(f, " to {}", to.display()),
+            RemoveAction::Deleted => Ok(()),
+        }
+    }
+}
+
+impl File
+{
+    /// Deletes the file
+    /// # Examples
+    /// ```no_run
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// let result = file.set_file("C:\\temp\\old.log").remove_file();
+    /// ```
+    pub fn remove_file(&mut self) -> Result<(), FileError>
+    {
+        self.remove(&RemoveOptions::new()).map(|_| ())
+    }
+
+    /// Removes the file, either deleting it (optionally overwriting it first) or moving it to a trash directory
+    /// # Examples
+    /// ```no_run
+    /// use davids_awesome_library::files::{File, RemoveOptions};
+    ///
+    /// let mut file = File::new();
+    /// let report = file.set_file("C:\\temp\\old.log").remove(RemoveOptions::new().trash_dir("C:\\temp\\trash").dry_run(true));
+    /// if let Ok(report) = report
+    /// {
+    ///     println!("{}", report);
+    /// }
+    /// ```
+    pub fn remove(&mut self, options: &RemoveOptions) -> Result<RemoveReport, FileError>
+    {
+        let path = PathBuf::from(&self.file);
+        if options.trash_dir.is_some() && options.overwrite_passes > 0
+        {
+            return Err(FileError::Io { path, source: io::Error::new(io::ErrorKind::InvalidInput, "A File Moved To The Trash Can't Be Securely Overwritten") });
+        }
+
+        let metadata = std::fs::symlink_metadata(&path).map_err(|error| FileError::from_io(&path, error))?;
+        if metadata.is_dir()
+        {
+            return Err(FileError::Io { path, source: io::Error::new(io::ErrorKind::InvalidInput, "Could Not Remove: it is a directory") });
+        }
+
+        let action = match &options.trash_dir
+        {
+            Some(trash_dir) => RemoveAction::MovedToTrash(trash_destination(&path, trash_dir)),
+            // Writing to a symlink would go through to the file it points at
+            None if options.overwrite_passes > 0 && !metadata.file_type().is_symlink() => RemoveAction::Overwritten(options.overwrite_passes),
+            None => RemoveAction::Deleted,
+        };
+
+        let report = RemoveReport {
+            path: path.clone(),
+            size: metadata.len(),
+            action,
+            dry_run: options.dry_run,
+        };
+        if options.dry_run
+        {
+            return Ok(report);
+        }
+
+        match &report.action
+        {
+            RemoveAction::MovedToTrash(to) => {
+                let trash_dir = options.trash_dir.as_ref().unwrap();
+                std::fs::create_dir_all(trash_dir).map_err(|error| describe(error, "Could Not Create Trash Directory", trash_dir))?;
+                // Renamed where it can be, copied (keeping its times and permissions) to a trash on another filesystem
+                let mut moved = File::new();
+                moved.set_file(&self.file);
+                moved.move_to(to, &CopyOptions::new()).map_err(|error| describe(error.into(), "Could Not Move To Trash", &path))?;
+            }
+            RemoveAction::Overwritten(passes) => {
+                overwrite(&path, metadata.len(), *passes).map_err(|error| describe(error, "Could Not Overwrite", &path))?;
+                std::fs::remove_file(&path).map_err(|error| describe(error, "Could Not Remove", &path))?;
+            }
+            RemoveAction::Deleted => {
+                std::fs::remove_file(&path).map_err(|error| describe(error, "Could Not Remove", &path))?;
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Keeps the kind of the error but adds what was being done, a missing file is still reported as FileError::NotFound
+fn describe(error: io::Error, action: &str, path: &Path) -> FileError
+{
+    if error.kind() == io::ErrorKind::NotFound
+    {
+        return FileError::NotFound { path: path.to_path_buf(), source: error };
+    }
+    FileError::Io { path: path.to_path_buf(), source: io::Error::new(error.kind(), format!("{}: {}", action, error)) }
+}
+
+/// Where the file ends up in the trash, a number is added to the name if something is already there
+fn trash_destination(path: &Path, trash_dir: &Path) -> PathBuf
+{
+    let name = path.file_name().unwrap_or_default();
+    let mut destination = trash_dir.join(name);
+    let mut count = 1;
+    while destination.exists()
+    {
+        let mut numbered = name.to_os_string();
+        numbered.push(format!(".{}", count));
+        destination = trash_dir.join(numbered);
+        count += 1;
+    }
+    destination
+}
+
+/// Writes over every byte of the file, zeros on the first pass, ones on the second and noise after that
+fn overwrite(path: &Path, length: u64, passes: u32) -> io::Result<()>
+{
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut seed = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|time| time.as_nanos() as u64).unwrap_or(0x9E37_79B9_7F4A_7C15) | 1;
+
+    for pass in 0..passes
+    {
+        file.seek(io::SeekFrom::Start(0))?;
+        let mut remaining = length;
+        while remaining > 0
+        {
+            match pass
+            {
+                0 => buffer.fill(0x00),
+                1 => buffer.fill(0xFF),
+                _ => {
+                    for byte in buffer.iter_mut()
+                    {
+                        // xorshift, only needs to look like noise
+                        seed ^= seed << 13;
+                        seed ^= seed >> 7;
+                        seed ^= seed << 17;
+                        *byte = seed as u8;
+                    }
+                }
+            }
+
+            let length = remaining.min(buffer.len() as u64) as usize;
+            file.write_all(&buffer[..length])?;
+            remaining -= length as u64;
+        }
+        file.sync_all()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::TestDir;
+
+    #[test]
+    fn remove_files() {
+
+        let dir = TestDir::new("remove");
+        let trash = dir.join("trash");
+        let path = dir.join("old.log");
+
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+        assert_eq!(file.remove_file().unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+        file.create_new("contents").unwrap();
+        let report = file.remove(RemoveOptions::new().trash_dir(&trash).dry_run(true)).unwrap();
+        assert_eq!(report.action, RemoveAction::MovedToTrash(trash.join("old.log")));
+        assert!(path.exists());
+        assert!(!trash.exists());
+        assert_eq!(report.to_string(), format!("Would move {} (8 bytes) to {}", path.display(), trash.join("old.log").display()));
+
+        file.remove(RemoveOptions::new().trash_dir(&trash)).unwrap();
+        file.create_new("contents").unwrap();
+        let report = file.remove(RemoveOptions::new().trash_dir(&trash)).unwrap();
+        assert_eq!(report.action, RemoveAction::MovedToTrash(trash.join("old.log.1")));
+        assert_eq!(std::fs::read_to_string(trash.join("old.log.1")).unwrap(), "contents");
+        assert!(!path.exists());
+
+        file.create_new("secret").unwrap();
+        assert!(file.remove(RemoveOptions::new().trash_dir(&trash).secure_overwrite(3)).is_err());
+        let report = file.remove(RemoveOptions::new().secure_overwrite(3)).unwrap();
+        assert_eq!(report.to_string(), format!("Overwrote and deleted {} (6 bytes) after 3 passes", path.display()));
+        assert!(!path.exists());
+
+        // Only the link goes, the file it points at is not overwritten
+        #[cfg(unix)]
+        {
+            let important = dir.join("important.txt");
+            std::fs::write(&important, "keep this").unwrap();
+            std::os::unix::fs::symlink(&important, &path).unwrap();
+            let report = file.remove(RemoveOptions::new().secure_overwrite(1)).unwrap();
+            assert_eq!(report.action, RemoveAction::Deleted);
+            assert!(std::fs::symlink_metadata(&path).is_err());
+            assert_eq!(std::fs::read_to_string(&important).unwrap(), "keep this");
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+
+use super::{File, FileError};
+
+/// Settings for File::find_lines
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions
+{
+    case_insensitive: bool,
+    max_matches: Option<usize>,
+    before: usize,
+    after: usize,
+}
+
+impl SearchOptions
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Ignore case when matching the pattern
+    pub fn case_insensitive(&mut self, enabled: bool) -> &mut Self
+    {
+        self.case_insensitive = enabled;
+        self
+    }
+
+    /// Stop searching after this many matching lines
+    pub fn max_matches(&mut self, count: usize) -> &mut Self
+    {
+        self.max_matches = Some(count);
+        self
+    }
+
+    /// Include this many lines before and after each match
+    pub fn context(&mut self, before: usize, after: usize) -> &mut Self
+    {
+        self.before = before;
+        self.after = after;
+        self
+    }
+}
+
+/// A line that matched the pattern given to File::find_lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineMatch
+{
+    /// Where the line is in the file, starting at 1
+    pub line_number: usize,
+    /// The line that matched, without its line ending
+    pub line: String,
+    /// The capture groups of the first match in the line, index 0 is the whole match and groups that didn't take part are None
+    pub captures: Vec<Option<String>>,
+    /// The named capture groups that took part in the first match in the line
+    pub named_captures: std::collections::BTreeMap<String, String>,
+    /// The lines leading up to the match, oldest first
+    pub before: Vec<String>,
+    /// The lines following the match
+    pub after: Vec<String>,
+}
+
+impl File
+{
+    /// Finds every line matching the regex pattern, along with its line number, capture groups and surrounding lines
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::files::{File, SearchOptions};
+    ///
+    /// let mut file = File::new();
+    /// let result = file.set_file("C:\\Windows\\WindowsUpdate.log").find_lines(r"error (0x[0-9a-f]+)", SearchOptions::new().case_insensitive(true).context(2, 2));
+    /// if let Ok(matches) = result
+    /// {
+    ///     for found in matches
+    ///     {
+    ///         println!("{}: {:?}", found.line_number, found.captures[1]);
+    ///     }
+    /// }
+    /// ```
+    pub fn find_lines(&mut self, pattern: &str, options: &SearchOptions) -> Result<Vec<LineMatch>, FileError>
+    {
+        let regex = regex::RegexBuilder::new(pattern).case_insensitive(options.case_insensitive).build()
+            .map_err(|source| FileError::InvalidPattern { path: std::path::PathBuf::from(&self.file), pattern: pattern.to_string(), source })?;
+
+        let mut matches: Vec<LineMatch> = Vec::new();
+        let mut previous: VecDeque<String> = VecDeque::with_capacity(options.before);
+        // Matches that still need lines added to their "after" context
+        let mut waiting = 0;
+
+        for (index, line) in self.lines()?.enumerate()
+        {
+            let line = line?;
+
+            let len = matches.len();
+            for found in matches[len - waiting..].iter_mut()
+            {
+                found.after.push(line.clone());
+            }
+            waiting = matches[len - waiting..].iter().filter(|found| found.after.len() < options.after).count();
+
+            let limit_reached = options.max_matches.is_some_and(|max| matches.len() >= max);
+            if limit_reached
+            {
+                if waiting == 0
+                {
+                    break;
+                }
+                continue;
+            }
+
+            if let Some(captures) = regex.captures(&line)
+            {
+                let named_captures = regex.capture_names()
+                    .flatten()
+                    .filter_map(|name| captures.name(name).map(|value| (name.to_string(), value.as_str().to_string())))
+                    .collect();
+
+                matches.push(LineMatch {
+                    line_number: index + 1,
+                    captures: captures.iter().map(|group| group.map(|value| value.as_str().to_string())).collect(),
+                    named_captures,
+                    line: line.clone(),
+                    before: previous.iter().cloned().collect(),
+                    after: Vec::new(),
+                });
+                if options.after > 0
+                {
+                    waiting += 1;
+                }
+            }
+
+            if options.before > 0
+            {
+                if previous.len() == options.before
+                {
+                    previous.pop_front();
+                }
+                previous.push_back(line);
+            }
+        }
+
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::TestDir;
+
+    #[test]
+    fn regex_search() {
+
+        let dir = TestDir::new("search");
+        let path = dir.join("search.log");
+        std::fs::write(&path, "one\nERROR code=1\ntwo\nthree\nerror code=2\nfour\nError code=3\n").unwrap();
+
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+
+        let matches = file.find_lines(r"error code=(?<code>\d)", &SearchOptions::new()).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 5);
+        assert_eq!(matches[0].captures, vec![Some("error code=2".to_string()), Some("2".to_string())]);
+        assert_eq!(matches[0].named_captures["code"], "2");
+
+        let matches = file.find_lines(r"error code=(\d)", SearchOptions::new().case_insensitive(true).max_matches(2).context(1, 2)).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].before, vec!["one"]);
+        assert_eq!(matches[0].after, vec!["two", "three"]);
+        assert_eq!(matches[1].line_number, 5);
+        assert_eq!(matches[1].before, vec!["three"]);
+        assert_eq!(matches[1].after, vec!["four", "Error code=3"]);
+
+        assert!(file.find_lines("(", &SearchOptions::new()).is_err());
+    }
+}
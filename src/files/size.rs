@@ -1,8 +1,381 @@
-// because size matters 
+// because size matters
 
 pub const BYTE: u64 = 1;
 pub const KILOBYTE: u64 = BYTE*1000;
 pub const MEGABYTE: u64 = KILOBYTE*1000;
 pub const GIGABYTE: u64 = MEGABYTE*1000;
-pub const TERRABYTE: u64 = GIGABYTE*1000;
-pub const PETABYTE: u64 = TERRABYTE*1000;
\ No newline at end of file
+pub const TERABYTE: u64 = GIGABYTE*1000;
+pub const PETABYTE: u64 = TERABYTE*1000;
+pub const EXABYTE: u64 = PETABYTE*1000;
+
+#[deprecated(note = "use TERABYTE")]
+pub const TERRABYTE: u64 = TERABYTE;
+
+pub const KIBIBYTE: u64 = BYTE*1024;
+pub const MEBIBYTE: u64 = KIBIBYTE*1024;
+pub const GIBIBYTE: u64 = MEBIBYTE*1024;
+pub const TEBIBYTE: u64 = GIBIBYTE*1024;
+pub const PEBIBYTE: u64 = TEBIBYTE*1024;
+pub const EXBIBYTE: u64 = PEBIBYTE*1024;
+
+const SI_UNITS: [(u64, &str); 6] = [(EXABYTE, "EB"), (PETABYTE, "PB"), (TERABYTE, "TB"), (GIGABYTE, "GB"), (MEGABYTE, "MB"), (KILOBYTE, "KB")];
+const IEC_UNITS: [(u64, &str); 6] = [(EXBIBYTE, "EiB"), (PEBIBYTE, "PiB"), (TEBIBYTE, "TiB"), (GIBIBYTE, "GiB"), (MEBIBYTE, "MiB"), (KIBIBYTE, "KiB")];
+
+/// Whether sizes are shown in powers of 1000 (KB, MB, GB) or powers of 1024 (KiB, MiB, GiB)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Units
+{
+    #[default]
+    Si,
+    Iec,
+}
+
+/// A number of bytes that can be shown as and read from text like "1.5 GB", "2 GiB" or "10k"
+/// # Examples
+/// ```
+/// use davids_awesome_library::files::size::{ByteSize, Units};
+///
+/// let size: ByteSize = "1.5 GB".parse().unwrap();
+/// assert_eq!(size.as_u64(), 1_500_000_000);
+/// assert_eq!(size.to_string(), "1.5 GB");
+/// assert_eq!(size.to_string_as(Units::Iec), "1.4 GiB");
+/// assert!(size > ByteSize::mb(500));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ByteSize(pub u64);
+
+impl ByteSize
+{
+    pub fn b(bytes: u64) -> Self
+    {
+        ByteSize(bytes)
+    }
+
+    pub fn kb(kilobytes: u64) -> Self
+    {
+        ByteSize(kilobytes.saturating_mul(KILOBYTE))
+    }
+
+    pub fn mb(megabytes: u64) -> Self
+    {
+        ByteSize(megabytes.saturating_mul(MEGABYTE))
+    }
+
+    pub fn gb(gigabytes: u64) -> Self
+    {
+        ByteSize(gigabytes.saturating_mul(GIGABYTE))
+    }
+
+    pub fn tb(terabytes: u64) -> Self
+    {
+        ByteSize(terabytes.saturating_mul(TERABYTE))
+    }
+
+    pub fn kib(kibibytes: u64) -> Self
+    {
+        ByteSize(kibibytes.saturating_mul(KIBIBYTE))
+    }
+
+    pub fn mib(mebibytes: u64) -> Self
+    {
+        ByteSize(mebibytes.saturating_mul(MEBIBYTE))
+    }
+
+    pub fn gib(gibibytes: u64) -> Self
+    {
+        ByteSize(gibibytes.saturating_mul(GIBIBYTE))
+    }
+
+    pub fn tib(tebibytes: u64) -> Self
+    {
+        ByteSize(tebibytes.saturating_mul(TEBIBYTE))
+    }
+
+    /// The number of bytes
+    pub fn as_u64(&self) -> u64
+    {
+        self.0
+    }
+
+    /// Adds the sizes, None if the total doesn't fit in a u64 (the + operator stops at the largest size instead)
+    pub fn checked_add(self, other: ByteSize) -> Option<ByteSize>
+    {
+        self.0.checked_add(other.0).map(ByteSize)
+    }
+
+    /// Subtracts the sizes, None if the other size is larger (the - operator stops at 0 instead)
+    pub fn checked_sub(self, other: ByteSize) -> Option<ByteSize>
+    {
+        self.0.checked_sub(other.0).map(ByteSize)
+    }
+
+    /// Multiplies the size, None if it doesn't fit in a u64 (the * operator stops at the largest size instead)
+    pub fn checked_mul(self, times: u64) -> Option<ByteSize>
+    {
+        self.0.checked_mul(times).map(ByteSize)
+    }
+
+    /// Shows the size in the largest unit it fills with one decimal place (eg. "1.5 GB" or "1.4 GiB"), anything under a kilobyte is shown in bytes
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::files::size::{ByteSize, Units};
+    ///
+    /// assert_eq!(ByteSize::kib(1536).to_string_as(Units::Iec), "1.5 MiB");
+    /// assert_eq!(ByteSize::b(512).to_string_as(Units::Si), "512 B");
+    /// ```
+    pub fn to_string_as(&self, units: Units) -> String
+    {
+        format_size(self.0, units, 1)
+    }
+}
+
+fn format_size(bytes: u64, units: Units, precision: usize) -> String
+{
+    let (table, base) = match units
+    {
+        Units::Si => (&SI_UNITS, 1000.0),
+        Units::Iec => (&IEC_UNITS, 1024.0),
+    };
+
+    let index = match table.iter().position(|(size, _)| bytes >= *size)
+    {
+        Some(index) => index,
+        None => return format!("{} B", bytes),
+    };
+
+    // Rounding can fill the next unit up, 999,999 bytes is "1.0 MB" rather than "1000.0 KB"
+    let (size, name) = table[index];
+    let shown: f64 = format!("{:.*}", precision, bytes as f64 / size as f64).parse().unwrap_or_default();
+    let (size, name) = if shown >= base && index > 0 { table[index - 1] } else { (size, name) };
+    format!("{:.*} {}", precision, bytes as f64 / size as f64, name)
+}
+
+/// Shows the size in SI units ("1.5 GB"), the alternate flag ("{:#}") switches to IEC units ("1.4 GiB")
+/// and a precision ("{:.2}") changes the number of decimal places
+impl std::fmt::Display for ByteSize
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let units = if f.alternate() { Units::Iec } else { Units::Si };
+        f.write_str(&format_size(self.0, units, f.precision().unwrap_or(1)))
+    }
+}
+
+impl std::str::FromStr for ByteSize
+{
+    type Err = std::io::Error;
+
+    /// Parses a number followed by an optional unit, ignoring case and spaces (eg. "500MB", "2 GiB", "10k", "1.5 tb").
+    /// A single letter or "KB" style unit is in powers of 1000, "KiB" style units are in powers of 1024.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid Byte Size");
+
+        let s = s.trim();
+        let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+
+        let multiplier = match unit.trim().to_ascii_lowercase().as_str()
+        {
+            "" | "b" => BYTE,
+            "k" | "kb" => KILOBYTE,
+            "m" | "mb" => MEGABYTE,
+            "g" | "gb" => GIGABYTE,
+            "t" | "tb" => TERABYTE,
+            "p" | "pb" => PETABYTE,
+            "e" | "eb" => EXABYTE,
+            "ki" | "kib" => KIBIBYTE,
+            "mi" | "mib" => MEBIBYTE,
+            "gi" | "gib" => GIBIBYTE,
+            "ti" | "tib" => TEBIBYTE,
+            "pi" | "pib" => PEBIBYTE,
+            "ei" | "eib" => EXBIBYTE,
+            _ => return Err(invalid()),
+        };
+
+        // Whole numbers are worked out exactly, only fractions go through floating point
+        if let Ok(whole) = number.parse::<u64>()
+        {
+            return whole.checked_mul(multiplier).map(ByteSize).ok_or_else(invalid);
+        }
+
+        let fraction: f64 = number.parse().map_err(|_| invalid())?;
+        let bytes = (fraction * multiplier as f64).round();
+        if !bytes.is_finite() || bytes >= u64::MAX as f64
+        {
+            return Err(invalid());
+        }
+        Ok(ByteSize(bytes as u64))
+    }
+}
+
+impl From<u64> for ByteSize
+{
+    fn from(bytes: u64) -> Self {
+        ByteSize(bytes)
+    }
+}
+
+impl From<ByteSize> for u64
+{
+    fn from(size: ByteSize) -> Self {
+        size.0
+    }
+}
+
+/// The arithmetic saturates, it stops at 0 or the largest size rather than overflowing (see checked_add and friends)
+impl std::ops::Add for ByteSize
+{
+    type Output = ByteSize;
+
+    fn add(self, other: ByteSize) -> ByteSize {
+        ByteSize(self.0.saturating_add(other.0))
+    }
+}
+
+impl std::ops::AddAssign for ByteSize
+{
+    fn add_assign(&mut self, other: ByteSize) {
+        self.0 = self.0.saturating_add(other.0);
+    }
+}
+
+impl std::ops::Sub for ByteSize
+{
+    type Output = ByteSize;
+
+    fn sub(self, other: ByteSize) -> ByteSize {
+        ByteSize(self.0.saturating_sub(other.0))
+    }
+}
+
+impl std::ops::SubAssign for ByteSize
+{
+    fn sub_assign(&mut self, other: ByteSize) {
+        self.0 = self.0.saturating_sub(other.0);
+    }
+}
+
+impl std::ops::Mul<u64> for ByteSize
+{
+    type Output = ByteSize;
+
+    fn mul(self, times: u64) -> ByteSize {
+        ByteSize(self.0.saturating_mul(times))
+    }
+}
+
+impl std::ops::Div<u64> for ByteSize
+{
+    type Output = ByteSize;
+
+    fn div(self, parts: u64) -> ByteSize {
+        ByteSize(self.0 / parts)
+    }
+}
+
+impl std::iter::Sum for ByteSize
+{
+    fn sum<I: Iterator<Item = ByteSize>>(iter: I) -> ByteSize {
+        iter.fold(ByteSize(0), |total, size| total + size)
+    }
+}
+
+/// Written out as the exact number of bytes so nothing is lost to rounding
+impl serde::Serialize for ByteSize
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+/// Read from either a number of bytes (524288000, or 1.5e9 rounded to the nearest byte) or text ("500 MB")
+impl<'de> serde::Deserialize<'de> for ByteSize
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor
+        {
+            type Value = ByteSize;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a number of bytes or a size like \"500 MB\"")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, bytes: u64) -> Result<ByteSize, E> {
+                Ok(ByteSize(bytes))
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, bytes: i64) -> Result<ByteSize, E> {
+                u64::try_from(bytes).map(ByteSize).map_err(|_| E::custom("Invalid Byte Size"))
+            }
+
+            fn visit_f64<E: serde::de::Error>(self, bytes: f64) -> Result<ByteSize, E> {
+                let bytes = bytes.round();
+                if !bytes.is_finite() || bytes < 0.0 || bytes >= u64::MAX as f64
+                {
+                    return Err(E::custom("Invalid Byte Size"));
+                }
+                Ok(ByteSize(bytes as u64))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, text: &str) -> Result<ByteSize, E> {
+                text.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_and_parse() {
+        assert_eq!(ByteSize::b(999).to_string(), "999 B");
+        assert_eq!(ByteSize(1_500_000_000).to_string(), "1.5 GB");
+        assert_eq!(format!("{:#}", ByteSize(1_500_000_000)), "1.4 GiB");
+        assert_eq!(format!("{:.2}", ByteSize::kb(1234)), "1.23 MB");
+
+        // Rounding up to a full unit moves on to the next one
+        assert_eq!(ByteSize(999_999).to_string(), "1.0 MB");
+        assert_eq!(ByteSize(999_950_000).to_string(), "1.0 GB");
+        assert_eq!(ByteSize(999_940_000).to_string(), "999.9 MB");
+        assert_eq!(format!("{:.3}", ByteSize(999_999)), "999.999 KB");
+        assert_eq!(format!("{:#}", ByteSize(1_048_575)), "1.0 MiB");
+        assert_eq!(format!("{:#}", ByteSize(1_000_000)), "976.6 KiB");
+
+        assert_eq!("500MB".parse::<ByteSize>().unwrap(), ByteSize::mb(500));
+        assert_eq!("2 GiB".parse::<ByteSize>().unwrap(), ByteSize::gib(2));
+        assert_eq!("10k".parse::<ByteSize>().unwrap(), ByteSize(10_000));
+        assert_eq!("0.5 kib".parse::<ByteSize>().unwrap(), ByteSize(512));
+        assert_eq!("42".parse::<ByteSize>().unwrap(), ByteSize(42));
+        assert!("ten MB".parse::<ByteSize>().is_err());
+        assert!("10 XB".parse::<ByteSize>().is_err());
+        assert!("99999999999 EB".parse::<ByteSize>().is_err());
+
+        let total: ByteSize = [ByteSize::mb(1), ByteSize::kb(500)].into_iter().sum();
+        assert_eq!(total, ByteSize(1_500_000));
+        assert_eq!(total - ByteSize::kb(500), ByteSize::mb(1));
+        assert_eq!(ByteSize::mb(1) * 3 / 2, ByteSize::kb(1500));
+        assert!(ByteSize::gib(1) > ByteSize::gb(1));
+
+        // Overflowing saturates, the checked versions say so instead
+        assert_eq!(ByteSize::kb(1) - ByteSize::mb(1), ByteSize(0));
+        assert_eq!(ByteSize(u64::MAX) + ByteSize(1), ByteSize(u64::MAX));
+        assert_eq!(ByteSize(u64::MAX / 2) * 3, ByteSize(u64::MAX));
+        assert_eq!(ByteSize::kb(1).checked_sub(ByteSize::mb(1)), None);
+        assert_eq!(ByteSize::mb(1).checked_sub(ByteSize::kb(1)), Some(ByteSize(999_000)));
+        assert_eq!(ByteSize(u64::MAX).checked_mul(2), None);
+
+        // Serialized as bytes, read back from either bytes or text
+        assert_eq!(serde_yaml::to_string(&ByteSize::kb(2)).unwrap().trim(), "2000");
+        assert_eq!(serde_yaml::from_str::<ByteSize>("2000").unwrap(), ByteSize::kb(2));
+        assert_eq!(serde_yaml::from_str::<ByteSize>("1.5 GB").unwrap(), ByteSize(1_500_000_000));
+        assert_eq!(serde_yaml::from_str::<ByteSize>("1.5e9").unwrap(), ByteSize(1_500_000_000));
+        assert_eq!(serde_yaml::from_str::<ByteSize>("1.5").unwrap(), ByteSize(2));
+        assert!(serde_yaml::from_str::<ByteSize>("-5").is_err());
+        assert!(serde_yaml::from_str::<ByteSize>("-1.5").is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use std::path::{Path, PathBuf};
+
+/// A fresh folder in the temp directory for a test, removed again when it is dropped (also when the test fails)
+pub(super) struct TestDir
+{
+    path: PathBuf,
+}
+
+impl TestDir
+{
+    /// Creates "davids_awesome_library_<name>" in the temp directory, emptying it first if an earlier run left it behind
+    pub(super) fn new(name: &str) -> Self
+    {
+        let path = std::env::temp_dir().join(format!("davids_awesome_library_{}", name));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        TestDir { path }
+    }
+
+    pub(super) fn path(&self) -> &Path
+    {
+        &self.path
+    }
+
+    pub(super) fn join<P: AsRef<Path>>(&self, name: P) -> PathBuf
+    {
+        self.path.join(name)
+    }
+}
+
+impl Drop for TestDir
+{
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Serves "body" over HTTP on a local port with the ETag "v1", honouring Range and If-Range requests. "/missing" answers 404,
+/// "/private" answers 401 without the "Bearer secret" token, "/cut" sends only the first half
+/// of the body before closing the connection (unless a range was asked for) and "/wrong-range" answers every range with all of it.
+pub(super) fn serve(body: Vec<u8>, connections: usize) -> String
+{
+    use std::io::{BufRead, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().take(connections)
+        {
+            let mut stream = stream.unwrap();
+            let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            reader.read_line(&mut request).unwrap();
+
+            let mut start = 0;
+            let mut authorized = false;
+            let mut changed = false;
+            loop
+            {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty()
+                {
+                    break;
+                }
+                if let Some(range) = header.to_ascii_lowercase().strip_prefix("range: bytes=")
+                {
+                    start = range.trim().trim_end_matches('-').parse().unwrap();
+                }
+                if header.trim() == "authorization: Bearer secret"
+                {
+                    authorized = true;
+                }
+                if header.to_ascii_lowercase().starts_with("if-range:") && !header.contains("\"v1\"")
+                {
+                    changed = true;
+                }
+            }
+
+            let response = if request.contains("/missing")
+            {
+                b"HTTP/1.1 404 Not Found\r\nContent-Length: 9\r\nConnection: close\r\n\r\nnot found".to_vec()
+            } else if request.contains("/private") && !authorized {
+                b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec()
+            } else if request.contains("/cut") && start == 0 {
+                let mut response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n", body.len()).into_bytes();
+                response.extend_from_slice(&body[..body.len() / 2]);
+                response
+            } else if start >= body.len() {
+                format!("HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */{}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", body.len()).into_bytes()
+            } else if request.contains("/wrong-range") && start > 0 {
+                let mut response = format!("HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes 0-{}/{}\r\nConnection: close\r\n\r\n", body.len(), body.len() - 1, body.len()).into_bytes();
+                response.extend_from_slice(&body);
+                response
+            } else if start > 0 && !changed {
+                let mut response = format!("HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {}-{}/{}\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n", body.len() - start, start, body.len() - 1, body.len()).into_bytes();
+                response.extend_from_slice(&body[start..]);
+                response
+            } else {
+                let mut response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n", body.len()).into_bytes();
+                response.extend_from_slice(&body);
+                response
+            };
+            let _ = stream.write_all(&response);
+        }
+    });
+    format!("http://{}", address)
+}
@@ -0,0 +1,418 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+use notify::event::{EventKind, ModifyKind, RenameMode};
+
+use super::follow::FollowedFile;
+use super::{File, FileError};
+
+/// A change to a watched file or folder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent
+{
+    Created(PathBuf),
+    Modified(PathBuf),
+    Removed(PathBuf),
+    /// Only reported when both sides of the rename are known, otherwise it shows up as Removed and/or Created
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+impl WatchEvent
+{
+    /// The path the event happened to, the new name for a rename
+    pub fn path(&self) -> &Path
+    {
+        match self
+        {
+            WatchEvent::Created(path) | WatchEvent::Modified(path) | WatchEvent::Removed(path) => path,
+            WatchEvent::Renamed { to, .. } => to,
+        }
+    }
+}
+
+/// Settings for Watcher::new
+#[derive(Debug, Clone)]
+pub struct WatchOptions
+{
+    recursive: bool,
+    force_polling: bool,
+    poll_interval: Duration,
+}
+
+impl Default for WatchOptions
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WatchOptions
+{
+    pub fn new() -> Self
+    {
+        WatchOptions {
+            recursive: false,
+            force_polling: false,
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Also watch everything in the sub folders of a watched folder
+    pub fn recursive(&mut self, enabled: bool) -> &mut Self
+    {
+        self.recursive = enabled;
+        self
+    }
+
+    /// Check for changes by polling instead of using the OS notifications (inotify on Linux),
+    /// which don't work on some network shares. Polling is also used when the OS notifications can't be set up.
+    pub fn force_polling(&mut self, enabled: bool) -> &mut Self
+    {
+        self.force_polling = enabled;
+        self
+    }
+
+    /// How often to check for changes when polling, 1 second by default
+    pub fn poll_interval(&mut self, interval: Duration) -> &mut Self
+    {
+        self.poll_interval = interval;
+        self
+    }
+}
+
+/// Watches a file or folder until it is dropped.
+/// Events are either handed to a callback (Watcher::with_callback) or read from the watcher itself (Watcher::new).
+pub struct Watcher
+{
+    _watcher: Box<dyn notify::Watcher + Send>,
+    receiver: Option<mpsc::Receiver<Result<WatchEvent, FileError>>>,
+    polling: bool,
+}
+
+impl Watcher
+{
+    /// Starts watching a file or folder, the events are read with recv, recv_timeout or by iterating over the watcher.
+    /// Watching a file keeps working when it is deleted and created again (eg. by an editor saving it).
+    /// # Examples
+    /// ```no_run
+    /// use davids_awesome_library::files::{Watcher, WatchOptions};
+    ///
+    /// let watcher = Watcher::new("C:\\temp", WatchOptions::new().recursive(true)).unwrap();
+    /// for event in watcher
+    /// {
+    ///     println!("{:?}", event);
+    /// }
+    /// ```
+    pub fn new<P: AsRef<Path>>(path: P, options: &WatchOptions) -> Result<Self, FileError>
+    {
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = Self::start(path.as_ref(), options, move |event| {
+            let _ = sender.send(event);
+        })?;
+        watcher.receiver = Some(receiver);
+        Ok(watcher)
+    }
+
+    /// Starts watching a file or folder, calling "callback" from a background thread for every event
+    /// # Examples
+    /// ```no_run
+    /// use davids_awesome_library::files::{Watcher, WatchOptions};
+    ///
+    /// let watcher = Watcher::with_callback("C:\\temp\\output.log", &WatchOptions::new(), |event| println!("{:?}", event)).unwrap();
+    /// ```
+    pub fn with_callback<P, F>(path: P, options: &WatchOptions, callback: F) -> Result<Self, FileError>
+    where
+        P: AsRef<Path>,
+        F: FnMut(Result<WatchEvent, FileError>) + Send + 'static,
+    {
+        Self::start(path.as_ref(), options, callback)
+    }
+
+    /// Waits for the next event, None when the events go to a callback instead
+    pub fn recv(&self) -> Option<Result<WatchEvent, FileError>>
+    {
+        self.receiver.as_ref()?.recv().ok()
+    }
+
+    /// Waits up to "timeout" for the next event
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Result<WatchEvent, FileError>>
+    {
+        self.receiver.as_ref()?.recv_timeout(timeout).ok()
+    }
+
+    /// Returns the next event if there already is one
+    pub fn try_recv(&self) -> Option<Result<WatchEvent, FileError>>
+    {
+        self.receiver.as_ref()?.try_recv().ok()
+    }
+
+    /// True if changes are found by polling rather than OS notifications
+    pub fn is_polling(&self) -> bool
+    {
+        self.polling
+    }
+
+    fn start<F>(path: &Path, options: &WatchOptions, callback: F) -> Result<Self, FileError>
+    where
+        F: FnMut(Result<WatchEvent, FileError>) + Send + 'static,
+    {
+        // A single file is watched through its folder so it can be followed through being deleted and created again
+        let (watch_path, only) = if path.is_dir()
+        {
+            (path.to_path_buf(), None)
+        } else {
+            let name = path.file_name().ok_or_else(|| FileError::from_io(path, io::Error::new(io::ErrorKind::InvalidInput, "Invalid Watch Path")))?;
+            let parent = match path.parent()
+            {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            let parent = parent.canonicalize().map_err(|error| FileError::from_io(path, error))?;
+            let file = parent.join(name);
+            (parent, Some(file))
+        };
+        let error_path = path.to_path_buf();
+        let mode = if options.recursive && only.is_none() { notify::RecursiveMode::Recursive } else { notify::RecursiveMode::NonRecursive };
+
+        // Shared so the same callback can be handed to the polling watcher if the OS one can't be set up
+        let callback = Arc::new(Mutex::new(callback));
+        let handler = move |event: notify::Result<notify::Event>| {
+            // A poisoned lock only means the callback panicked on an earlier event, it can still be handed the next one
+            let mut callback = callback.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            match event
+            {
+                Ok(event) => {
+                    for event in convert(event)
+                    {
+                        let wanted = match (&only, &event)
+                        {
+                            (None, _) => true,
+                            (Some(file), WatchEvent::Renamed { from, to }) => from == file || to == file,
+                            (Some(file), event) => event.path() == file,
+                        };
+                        if wanted
+                        {
+                            (*callback)(Ok(event));
+                        }
+                    }
+                }
+                Err(error) => (*callback)(Err(watch_error(&error_path, error))),
+            }
+        };
+
+        let config = notify::Config::default().with_poll_interval(options.poll_interval);
+        if !options.force_polling
+        {
+            let native: notify::Result<notify::RecommendedWatcher> = notify::Watcher::new(handler.clone(), config);
+            if let Ok(mut native) = native
+            {
+                if notify::Watcher::watch(&mut native, &watch_path, mode).is_ok()
+                {
+                    return Ok(Watcher { _watcher: Box::new(native), receiver: None, polling: false });
+                }
+            }
+        }
+
+        let mut polling = notify::PollWatcher::new(handler, config).map_err(|error| watch_error(path, error))?;
+        notify::Watcher::watch(&mut polling, &watch_path, mode).map_err(|error| watch_error(path, error))?;
+        Ok(Watcher { _watcher: Box::new(polling), receiver: None, polling: true })
+    }
+}
+
+impl Iterator for Watcher
+{
+    type Item = Result<WatchEvent, FileError>;
+
+    /// Blocks until the next event
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv()
+    }
+}
+
+/// Turns a notify event into ours, dropping the ones nobody asked for (eg. reads)
+fn convert(event: notify::Event) -> Vec<WatchEvent>
+{
+    let paths = event.paths;
+    match event.kind
+    {
+        EventKind::Create(_) => paths.into_iter().map(WatchEvent::Created).collect(),
+        EventKind::Remove(_) => paths.into_iter().map(WatchEvent::Removed).collect(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+            let mut paths = paths.into_iter();
+            let from = paths.next().unwrap();
+            let to = paths.next().unwrap();
+            vec![WatchEvent::Renamed { from, to }]
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => paths.into_iter().map(WatchEvent::Removed).collect(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => paths.into_iter().map(WatchEvent::Created).collect(),
+        // Which side of the rename this is can only be told by looking
+        EventKind::Modify(ModifyKind::Name(_)) => paths.into_iter()
+            .map(|path| if path.exists() { WatchEvent::Created(path) } else { WatchEvent::Removed(path) })
+            .collect(),
+        EventKind::Modify(_) => paths.into_iter().map(WatchEvent::Modified).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The error for the path the event was about, or the watched path if it doesn't say
+fn watch_error(watched: &Path, error: notify::Error) -> FileError
+{
+    let path = error.paths.first().cloned().unwrap_or_else(|| watched.to_path_buf());
+    let source = match error.kind
+    {
+        notify::ErrorKind::Io(error) => error,
+        notify::ErrorKind::PathNotFound => io::Error::new(io::ErrorKind::NotFound, "Path Not Found"),
+        _ => io::Error::other(format!("Could Not Watch: {}", error)),
+    };
+    FileError::from_io(path, source)
+}
+
+/// Yields the lines appended to a file, created by File::tail
+pub struct Tail
+{
+    followed: FollowedFile,
+    watcher: Watcher,
+    poll_interval: Duration,
+}
+
+impl File
+{
+    /// Starts watching the file for changes, see Watcher::new
+    /// # Examples
+    /// ```no_run
+    /// use davids_awesome_library::files::{File, WatchOptions};
+    ///
+    /// let mut file = File::new();
+    /// let watcher = file.set_file("C:\\temp\\output.log").watch(&WatchOptions::new()).unwrap();
+    /// while let Some(event) = watcher.recv()
+    /// {
+    ///     println!("{:?}", event);
+    /// }
+    /// ```
+    pub fn watch(&self, options: &WatchOptions) -> Result<Watcher, FileError>
+    {
+        Watcher::new(&self.file, options)
+    }
+
+    /// Delivers lines as they are appended to the file, like "tail -f". Lines already in the file are skipped.
+    /// Keeps following the file when it is truncated, or replaced (eg. by log rotation).
+    /// # Examples
+    /// ```no_run
+    /// let mut file = davids_awesome_library::files::File::new();
+    /// for line in file.set_file("C:\\temp\\output.log").tail().unwrap()
+    /// {
+    ///     println!("{}", line.unwrap());
+    /// }
+    /// ```
+    pub fn tail(&self) -> Result<Tail, FileError>
+    {
+        let options = WatchOptions::new();
+        Ok(Tail {
+            followed: FollowedFile::new(&self.file).map_err(|error| self.io_error(error))?,
+            watcher: Watcher::new(&self.file, &options)?,
+            poll_interval: options.poll_interval,
+        })
+    }
+}
+
+impl Tail
+{
+    /// Waits up to "timeout" for the next line, Ok(None) if nothing was appended in time
+    pub fn next_timeout(&mut self, timeout: Duration) -> Result<Option<String>, FileError>
+    {
+        let deadline = std::time::Instant::now() + timeout;
+        loop
+        {
+            let replaced = self.followed.is_replaced();
+
+            if let Some(line) = self.followed.next_line().map_err(|error| FileError::from_io(self.followed.path(), error))?
+            {
+                return Ok(Some(line));
+            }
+            if replaced && self.followed.reopen().is_ok()
+            {
+                continue;
+            }
+
+            let now = std::time::Instant::now();
+            if now >= deadline
+            {
+                return Ok(None);
+            }
+            // Woken up early by a change, the interval only covers events that were missed
+            let _ = self.watcher.recv_timeout(self.poll_interval.min(deadline - now));
+        }
+    }
+}
+
+impl Iterator for Tail
+{
+    type Item = Result<String, FileError>;
+
+    /// Blocks until the next line is appended
+    fn next(&mut self) -> Option<Self::Item> {
+        loop
+        {
+            match self.next_timeout(Duration::from_secs(60))
+            {
+                Ok(Some(line)) => return Some(Ok(line)),
+                Ok(None) => continue,
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::files::test_support::TestDir;
+
+    #[test]
+    fn watch_and_tail() {
+
+        let test_dir = TestDir::new("watch");
+        let dir = test_dir.path().canonicalize().unwrap();
+        let path = dir.join("watched.log");
+        std::fs::write(&path, "already there\n").unwrap();
+
+        let mut file = File::new();
+        file.set_file(path.to_str().unwrap());
+
+        // Changes to other files in the folder are left out
+        let watcher = file.watch(&WatchOptions::new()).unwrap();
+        std::fs::write(dir.join("other.log"), "ignored").unwrap();
+        std::fs::write(&path, "changed\n").unwrap();
+        let event = watcher.recv_timeout(std::time::Duration::from_secs(5)).unwrap().unwrap();
+        assert_eq!(event.path(), path);
+        drop(watcher);
+
+        // Polling picks up removals in a folder too
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        let poller = Watcher::with_callback(&dir, WatchOptions::new().force_polling(true).poll_interval(std::time::Duration::from_millis(50)), move |event| {
+            seen.lock().unwrap().push(event.unwrap());
+        }).unwrap();
+        assert!(poller.is_polling());
+        std::fs::remove_file(dir.join("other.log")).unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !events.lock().unwrap().contains(&WatchEvent::Removed(dir.join("other.log"))) && std::time::Instant::now() < deadline
+        {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert!(events.lock().unwrap().contains(&WatchEvent::Removed(dir.join("other.log"))));
+        drop(poller);
+
+        // Only appended lines come through, across the file being replaced
+        let mut tail = file.tail().unwrap();
+        file.append_line("first").unwrap();
+        assert_eq!(tail.next_timeout(std::time::Duration::from_secs(5)).unwrap().unwrap(), "first");
+        std::fs::remove_file(&path).unwrap();
+        std::fs::write(&path, "second\n").unwrap();
+        assert_eq!(tail.next_timeout(std::time::Duration::from_secs(5)).unwrap().unwrap(), "second");
+        assert_eq!(tail.next_timeout(std::time::Duration::from_millis(100)).unwrap(), None);
+    }
+}
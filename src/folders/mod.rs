@@ -9,6 +9,13 @@ pub struct FolderSingle
     dir: String
 }
 
+impl Default for FolderSingle
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FolderSingle
 {
     pub fn new() -> Self
@@ -21,6 +28,20 @@ impl FolderSingle
         self.dir = dir.to_string();
         self
     }
+
+    /// Starts watching the folder for files being created, modified, removed or renamed, see files::Watcher::new
+    /// # Examples
+    /// ```no_run
+    /// use davids_awesome_library::files::WatchOptions;
+    /// use davids_awesome_library::folders::FolderSingle;
+    ///
+    /// let mut folder = FolderSingle::new();
+    /// let watcher = folder.set_root_directory("C:\\temp").watch(WatchOptions::new().recursive(true)).unwrap();
+    /// ```
+    pub fn watch(&self, options: &crate::files::WatchOptions) -> Result<crate::files::Watcher, crate::files::FileError>
+    {
+        crate::files::Watcher::new(&self.dir, options)
+    }
 }
 
 pub struct FolderArray
@@ -28,6 +49,13 @@ pub struct FolderArray
     dirs: Vec<String>
 }
 
+impl Default for FolderArray
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FolderArray
 {
     pub fn new() -> Self
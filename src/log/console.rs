@@ -0,0 +1,94 @@
+use std::io::{IsTerminal, Write};
+
+use crate::log::{Level, LogFile};
+
+/// Where the lines written to the log file are mirrored to on the console
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Console
+{
+    /// Only the log file is written to
+    Off,
+    /// Every line is also printed to stdout
+    #[default]
+    Stdout,
+    /// Every line is also printed to stderr, keeps stdout free for machine-readable output
+    Stderr,
+}
+
+impl LogFile
+{
+    /// Sets where lines are mirrored to on the console, stdout by default. The log file is written to either way.
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::log::{Console, LogFile};
+    ///
+    /// let mut test = LogFile::new();
+    /// test.set_console(Console::Stderr);
+    /// test.write_log("success");
+    /// ```
+    pub fn set_console(&mut self, console: Console) -> &mut Self
+    {
+        self.console = console;
+        self
+    }
+
+    /// Colors the console lines by level (errors in red, warnings in yellow, ...).
+    /// Only applies when the console is a terminal, redirected output is never colored.
+    pub fn set_color(&mut self, enabled: bool) -> &mut Self
+    {
+        self.color = enabled;
+        self
+    }
+
+    /// Mirrors an already rendered line (including its newline) to the console
+    pub(crate) fn echo(&self, level: Level, line: &str)
+    {
+        match self.console
+        {
+            Console::Off => {}
+            Console::Stdout => {
+                let stdout = std::io::stdout();
+                let color = self.color && stdout.is_terminal();
+                let _ = stdout.lock().write_all(paint(level, line, color).as_bytes());
+            }
+            Console::Stderr => {
+                let stderr = std::io::stderr();
+                let color = self.color && stderr.is_terminal();
+                let _ = stderr.lock().write_all(paint(level, line, color).as_bytes());
+            }
+        }
+    }
+}
+
+/// Wraps the line in the ANSI color for the level, the trailing newline is kept outside of the color
+fn paint(level: Level, line: &str, color: bool) -> String
+{
+    if !color
+    {
+        return line.to_string();
+    }
+
+    let code = match level
+    {
+        Level::Trace => "90",
+        Level::Debug => "36",
+        Level::Info => "32",
+        Level::Warn => "33",
+        Level::Error => "31",
+    };
+
+    let text = line.trim_end_matches('\n');
+    format!("\x1b[{}m{}\x1b[0m{}", code, text, &line[text.len()..])
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn paint_by_level() {
+        assert_eq!(paint(Level::Error, "failed\n", true), "\x1b[31mfailed\x1b[0m\n");
+        assert_eq!(paint(Level::Error, "failed\n", false), "failed\n");
+    }
+}
@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+/// Why writing to or managing the log file failed, always includes the path of the log file involved
+#[derive(Debug)]
+pub enum LogError
+{
+    /// The log file could not be opened or created (missing directory, no permission, read-only, ...)
+    Open { path: PathBuf, source: std::io::Error },
+    /// The log file was opened but the line could not be written to it
+    Write { path: PathBuf, source: std::io::Error },
+    /// The log file could not be rolled over into an archive
+    Rotate { path: PathBuf, source: std::io::Error },
+    /// The log file could not be removed
+    Remove { path: PathBuf, source: std::io::Error },
+    /// A rolled over archive could not be gzipped, the path is the one of the archive
+    Compress { path: PathBuf, source: std::io::Error },
+}
+
+impl LogError
+{
+    /// The path of the log file the error happened on
+    pub fn path(&self) -> &std::path::Path
+    {
+        match self
+        {
+            LogError::Open { path, .. }
+            | LogError::Write { path, .. }
+            | LogError::Rotate { path, .. }
+            | LogError::Remove { path, .. }
+            | LogError::Compress { path, .. } => path,
+        }
+    }
+
+    fn io_error(&self) -> &std::io::Error
+    {
+        match self
+        {
+            LogError::Open { source, .. }
+            | LogError::Write { source, .. }
+            | LogError::Rotate { source, .. }
+            | LogError::Remove { source, .. }
+            | LogError::Compress { source, .. } => source,
+        }
+    }
+}
+
+impl std::fmt::Display for LogError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = match self
+        {
+            LogError::Open { .. } => "Could Not Open Log File",
+            LogError::Write { .. } => "Could Not Write To Log File",
+            LogError::Rotate { .. } => "Could Not Rotate Log File",
+            LogError::Remove { .. } => "Could Not Remove Log File",
+            LogError::Compress { .. } => "Could Not Compress Log Archive",
+        };
+        write!(f, "{} {}: {}", action, self.path().display(), self.io_error())
+    }
+}
+
+impl std::error::Error for LogError
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.io_error())
+    }
+}
+
+impl From<LogError> for std::io::Error
+{
+    fn from(error: LogError) -> Self {
+        std::io::Error::new(error.io_error().kind(), error)
+    }
+}
@@ -0,0 +1,102 @@
+use crate::log::{Level, LogFile, Record, SharedLog};
+
+/// Forwards everything sent through the `log` crate macros (`log::info!`, `log::warn!`, ...) to a LogFile
+struct GlobalLogger
+{
+    log: SharedLog,
+}
+
+impl ::log::Log for GlobalLogger
+{
+    fn enabled(&self, metadata: &::log::Metadata) -> bool {
+        self.log.enabled(Level::from(metadata.level()))
+    }
+
+    fn log(&self, record: &::log::Record) {
+        if !self.enabled(record.metadata())
+        {
+            return;
+        }
+
+        let mut entry = Record::new(Level::from(record.level()), &record.args().to_string());
+        entry.field("target", record.target());
+        self.log.write_record(&entry);
+    }
+
+    /// Waits until everything queued for a background writer has been written out
+    fn flush(&self) {
+        self.log.flush();
+    }
+}
+
+impl From<::log::Level> for Level
+{
+    fn from(level: ::log::Level) -> Self {
+        match level
+        {
+            ::log::Level::Trace => Level::Trace,
+            ::log::Level::Debug => Level::Debug,
+            ::log::Level::Info => Level::Info,
+            ::log::Level::Warn => Level::Warn,
+            ::log::Level::Error => Level::Error,
+        }
+    }
+}
+
+impl From<Level> for ::log::LevelFilter
+{
+    fn from(level: Level) -> Self {
+        match level
+        {
+            Level::Trace => ::log::LevelFilter::Trace,
+            Level::Debug => ::log::LevelFilter::Debug,
+            Level::Info => ::log::LevelFilter::Info,
+            Level::Warn => ::log::LevelFilter::Warn,
+            Level::Error => ::log::LevelFilter::Error,
+        }
+    }
+}
+
+impl LogFile
+{
+    /// Installs the LogFile as the global logger for the `log` crate, so every `log::info!`, `log::warn!`, etc.
+    /// in the process (including dependencies) is written out to it. Can only be done once per process.
+    /// The global logger is never dropped, call `log::logger().flush()` before the process exits.
+    /// # Examples
+    /// ```
+    /// let mut test = davids_awesome_library::log::LogFile::new();
+    /// test.custom_file_path(std::env::temp_dir()).file_name("global.log");
+    /// test.init_global().unwrap();
+    /// log::info!("success");
+    /// log::logger().flush();
+    /// ```
+    pub fn init_global(self) -> Result<(), ::log::SetLoggerError>
+    {
+        self.into_shared().init_global()
+    }
+}
+
+impl SharedLog
+{
+    /// Installs the handle as the global logger for the `log` crate, the handle itself can still be used afterwards.
+    /// Can only be done once per process.
+    /// The global logger keeps a handle that is never dropped, so with a background writer anything still queued
+    /// when the process exits is lost. Call `log::logger().flush()` (or "flush" on the handle) before exiting,
+    /// it waits until the queue has been written out.
+    /// # Examples
+    /// ```
+    /// let mut test = davids_awesome_library::log::LogFile::new();
+    /// test.custom_file_path(std::env::temp_dir()).file_name("global.log");
+    /// let log = test.into_background(1024);
+    /// log.init_global().unwrap();
+    /// log::info!("through the macro");
+    /// log.write_log("through the handle");
+    /// log::logger().flush();
+    /// ```
+    pub fn init_global(&self) -> Result<(), ::log::SetLoggerError>
+    {
+        ::log::set_boxed_logger(Box::new(GlobalLogger { log: self.clone() }))?;
+        ::log::set_max_level(self.inner_level().into());
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::log::{Console, Level, LogError, LogFile, Record};
+
+/// Where lines go when the log file itself can't be written to
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Fallback
+{
+    /// The line is dropped
+    None,
+    /// The line is printed to stderr
+    #[default]
+    Stderr,
+    /// The line is kept in memory until it is collected with "take_buffered"
+    Memory,
+    /// The line is appended to another file instead
+    Path(PathBuf),
+}
+
+impl LogFile
+{
+    /// Sets where lines go when the log file can't be written to, stderr by default.
+    /// Only used by the infallible writes ("write_log", "write_record", ...), the "try_" versions return the error instead.
+    /// A rotation or compression that failed is reported there too, as an "Error" record.
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::log::{Fallback, LogFile};
+    ///
+    /// let mut test = LogFile::new();
+    /// test.custom_file_path("/this/directory/does/not/exist").set_fallback(Fallback::Memory);
+    /// test.write_log("kept in memory");
+    /// assert_eq!(test.take_buffered().len(), 1);
+    /// ```
+    pub fn set_fallback(&mut self, fallback: Fallback) -> &mut Self
+    {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Hands over (and clears) the lines kept in memory by "Fallback::Memory"
+    pub fn take_buffered(&mut self) -> Vec<String>
+    {
+        std::mem::take(&mut self.buffered)
+    }
+
+    /// Sends a rendered line (including its newline) to the fallback
+    pub(crate) fn write_fallback(&mut self, line: &str)
+    {
+        match &self.fallback
+        {
+            Fallback::None => {}
+            Fallback::Stderr => {
+                // Already mirrored there, no need to print it twice
+                if self.console != Console::Stderr
+                {
+                    let _ = std::io::stderr().lock().write_all(line.as_bytes());
+                }
+            }
+            Fallback::Memory => self.buffered.push(line.trim_end_matches('\n').to_string()),
+            Fallback::Path(path) => {
+                let file = std::fs::OpenOptions::new().create(true).append(true).open(path);
+                if let Ok(mut file) = file
+                {
+                    let _ = file.write_all(line.as_bytes());
+                }
+            }
+        }
+    }
+
+    /// Sends an error the write itself got past (eg. a failed rotation) to the fallback as an "Error" record
+    pub(crate) fn write_fallback_error(&mut self, error: LogError)
+    {
+        let line = format!("{}\n", self.format.render(&Record::new(Level::Error, &error.to_string())));
+        self.write_fallback(&line);
+    }
+}
@@ -0,0 +1,114 @@
+use super::record::TIMESTAMP_FORMAT;
+use super::Record;
+
+/// How each record is laid out when it is written out to the log file
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Format
+{
+    /// "2024-11-14 09:30:00 | Message: disk almost full | free=2GB", the layout from before records had levels.
+    /// Read back by LogReader there are two limits: there is no level, so every record reads as "Info",
+    /// and a message ending in " | key=value" can't be told apart from a field, so that part comes back as one.
+    /// Use JsonLines when the records have to be read back exactly.
+    #[default]
+    Plain,
+    /// The plain layout with a level column, "2024-11-14 09:30:00 | WARN | Message: disk almost full | free=2GB".
+    /// A message ending in " | key=value" is read back as a field, the same as with Plain.
+    PlainWithLevel,
+    /// One JSON object per line with an RFC 3339 timestamp, the level, the message and the fields
+    /// eg. {"timestamp":"2024-11-14T09:30:00.000+01:00","level":"WARN","message":"disk almost full","fields":{"free":"2GB"}}
+    JsonLines,
+    /// A custom layout where "{timestamp}", "{rfc3339}", "{level}", "{message}" and "{fields}" are filled in,
+    /// a single field can be filled in with "{field:name}"
+    Template(String),
+}
+
+impl Format
+{
+    /// Renders the record as a single line (without the trailing newline)
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::log::{Format, Level, Record};
+    ///
+    /// let format = Format::Template("[{level}] {message}".to_string());
+    /// assert_eq!(format.render(&Record::new(Level::Warn, "disk almost full")), "[WARN] disk almost full");
+    /// ```
+    pub fn render(&self, record: &Record) -> String
+    {
+        match self
+        {
+            Format::Plain => record.to_line(false),
+            Format::PlainWithLevel => record.to_line(true),
+            Format::JsonLines => Self::render_json(record),
+            Format::Template(template) => Self::render_template(template, record),
+        }
+    }
+
+    fn render_json(record: &Record) -> String
+    {
+        let mut fields = serde_json::Map::new();
+        for (key, value) in record.fields()
+        {
+            fields.insert(key.clone(), serde_json::Value::String(value.clone()));
+        }
+
+        serde_json::json!({
+            "timestamp": record.timestamp().to_rfc3339_opts(chrono::SecondsFormat::Millis, false),
+            "level": record.level().as_str(),
+            "message": record.message(),
+            "fields": fields,
+        }).to_string()
+    }
+
+    fn render_template(template: &str, record: &Record) -> String
+    {
+        let mut line = String::with_capacity(template.len() + record.message().len());
+        let mut rest = template;
+
+        // Placeholders are filled in a single pass, so a message containing "{level}" is left as is
+        while let Some(start) = rest.find('{')
+        {
+            line.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let end = after.find('}');
+            if end.is_none()
+            {
+                rest = &rest[start..];
+                break;
+            }
+            let end = end.unwrap();
+
+            let placeholder = &after[..end];
+            match placeholder
+            {
+                "timestamp" => line.push_str(&record.timestamp().format(TIMESTAMP_FORMAT).to_string()),
+                "rfc3339" => line.push_str(&record.timestamp().to_rfc3339_opts(chrono::SecondsFormat::Millis, false)),
+                "level" => line.push_str(record.level().as_str()),
+                "message" => line.push_str(record.message()),
+                "fields" => {
+                    let fields: Vec<String> = record.fields().iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+                    line.push_str(&fields.join(" "));
+                }
+                _ => match placeholder.strip_prefix("field:")
+                {
+                    Some(name) => {
+                        let value = record.fields().iter().find(|(key, _)| key == name);
+                        if let Some((_, value)) = value
+                        {
+                            line.push_str(value);
+                        }
+                    }
+                    // Unknown placeholders are kept as they were written
+                    None => {
+                        line.push('{');
+                        line.push_str(placeholder);
+                        line.push('}');
+                    }
+                },
+            }
+            rest = &after[end + 1..];
+        }
+
+        line.push_str(rest);
+        line
+    }
+}
@@ -0,0 +1,51 @@
+/// The severity of a log record, ordered from the most verbose to the most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level
+{
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level
+{
+    /// The upper case name of the level as it is written out to the log file.
+    pub fn as_str(&self) -> &'static str
+    {
+        match self
+        {
+            Level::Trace => "TRACE",
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+        }
+    }
+}
+
+impl std::fmt::Display for Level
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Level
+{
+    type Err = std::io::Error;
+
+    /// Parses a level name, ignoring case (eg. "warn", "WARN", "Warning")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str()
+        {
+            "TRACE" => Ok(Level::Trace),
+            "DEBUG" => Ok(Level::Debug),
+            "INFO" => Ok(Level::Info),
+            "WARN" | "WARNING" => Ok(Level::Warn),
+            "ERROR" => Ok(Level::Error),
+            _ => Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Unknown Log Level")),
+        }
+    }
+}
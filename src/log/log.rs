@@ -1,7 +1,6 @@
-#[cfg(target_os="windows")]
-use std::os::windows::fs::MetadataExt;
+use std::path::Path;
 
-use crate::log::LogFile;
+use crate::log::{Format, Level, LogError, LogFile, Record};
 
 impl LogFile
 {
@@ -13,13 +12,13 @@ impl LogFile
     /// test.default_file_path();
     /// test.write_log("success");
     /// ```
-
     pub fn default_file_path(&mut self) -> &mut Self {
         
-        let exe_name = std::env::current_exe().unwrap().display().to_string().split("\\").last().unwrap().to_string();
-        let exe_path = std::env::current_exe().unwrap().display().to_string().replace(&exe_name, "");
-        self.dir = exe_path;
-        self.update_path();
+        if let Ok(exe_dir) = crate::env::get_exe_dir()
+        {
+            self.dir = exe_dir;
+            self.update_path();
+        }
         self
     }
 
@@ -27,12 +26,11 @@ impl LogFile
     /// # Examples
     /// ```
     /// let mut test = davids_awesome_library::log::LogFile::new();
-    /// test.custom_file_path("..\\");
+    /// test.custom_file_path(std::env::temp_dir());
     /// test.write_log("success");
     /// ```
-
-    pub fn custom_file_path(&mut self, filepath: &str) -> &mut Self {
-        self.dir = filepath.to_string();
+    pub fn custom_file_path<P: AsRef<Path>>(&mut self, filepath: P) -> &mut Self {
+        self.dir = filepath.as_ref().to_path_buf();
         self.update_path();
         self
     }
@@ -44,34 +42,192 @@ impl LogFile
     /// test.file_name("log.log");
     /// test.write_log("success");
     /// ```
-
-    pub fn file_name(&mut self, filename: &str) -> &mut Self {
-        self.name = filename.to_string();
+    pub fn file_name<P: AsRef<Path>>(&mut self, filename: P) -> &mut Self {
+        self.name = filename.as_ref().to_path_buf();
         self.update_path();
         self
     }
 
-    /// Writes out the custom message to the log file
+    /// The full path of the log file that is written to
     /// # Examples
     /// ```
     /// let mut test = davids_awesome_library::log::LogFile::new();
+    /// test.default_file_path();
+    /// assert!(test.path().ends_with("output.log"));
+    /// ```
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Sets the minimum level a record needs for it to be written out, "Info" by default
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::log::{Level, LogFile};
+    ///
+    /// let mut test = LogFile::new();
+    /// test.set_level(Level::Warn);
+    /// test.info("this is not written out");
+    /// test.warn("this is");
+    /// ```
+    pub fn set_level(&mut self, level: Level) -> &mut Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets how each record is laid out in the log file, plain text by default
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::log::{Format, LogFile};
+    ///
+    /// let mut test = LogFile::new();
+    /// test.set_format(Format::JsonLines);
     /// test.write_log("success");
     /// ```
+    pub fn set_format(&mut self, format: Format) -> &mut Self {
+        self.format = format;
+        self
+    }
+
+    /// Returns true if a record of this level would be written out
+    pub fn enabled(&self, level: Level) -> bool
+    {
+        level >= self.level
+    }
 
+    /// Writes out the custom message to the log file at the "Info" level
+    /// # Examples
+    /// ```
+    /// let mut test = davids_awesome_library::log::LogFile::new();
+    /// test.write_log("success");
+    /// ```
     pub fn write_log(&mut self, message: &str)
     {
-        use std::io::Write;
-        let file_wrapped = std::fs::OpenOptions::new().write(true).create(true).append(true).open(&self.path);
-        if file_wrapped.is_err()
+        self.write_record(&Record::new(Level::Info, message));
+    }
+
+    /// Writes out a record to the log file if its level is at or above the minimum level
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::log::{Level, LogFile, Record};
+    ///
+    /// let mut test = LogFile::new();
+    /// test.write_record(Record::new(Level::Error, "install failed").field("code", "1603"));
+    /// ```
+    pub fn write_record(&mut self, record: &Record)
+    {
+        if !self.enabled(record.level())
         {
             return;
         }
-        
-        let mut file = file_wrapped.unwrap();
-        let new_line = format!("{} | Message: {}\n", chrono::offset::Local::now().format("%Y-%m-%d %H:%M:%S"), message);
-        println!("{}", new_line);
-        let _ = file.write(new_line.as_bytes());
-        let _ = file.flush();
+
+        let new_line = format!("{}\n", self.format.render(record));
+        // A failed rotation shouldn't stop the record from being written, only be reported
+        if let Err(source) = self.rotate_if_needed(new_line.len() as u64)
+        {
+            self.write_fallback_error(LogError::Rotate { path: self.path.clone(), source });
+        }
+
+        self.echo(record.level(), &new_line);
+        if self.write_line(&new_line).is_err()
+        {
+            self.write_fallback(&new_line);
+        }
+    }
+
+    /// Writes out the custom message to the log file at the "Info" level, returning why it failed instead of using the fallback
+    /// # Examples
+    /// ```
+    /// let mut test = davids_awesome_library::log::LogFile::new();
+    /// if let Err(error) = test.try_write_log("success")
+    /// {
+    ///     eprintln!("{}", error);
+    /// }
+    /// ```
+    pub fn try_write_log(&mut self, message: &str) -> Result<(), LogError>
+    {
+        self.try_write_record(&Record::new(Level::Info, message))
+    }
+
+    /// Writes out a record to the log file if its level is at or above the minimum level,
+    /// returning why it failed instead of using the fallback
+    pub fn try_write_record(&mut self, record: &Record) -> Result<(), LogError>
+    {
+        if !self.enabled(record.level())
+        {
+            return Ok(());
+        }
+
+        let new_line = format!("{}\n", self.format.render(record));
+        self.rotate_if_needed(new_line.len() as u64).map_err(|source| LogError::Rotate { path: self.path.clone(), source })?;
+
+        self.echo(record.level(), &new_line);
+        self.write_line(&new_line)
+    }
+
+    /// Writes an already rendered line (including its newline) to the log file
+    fn write_line(&mut self, line: &str) -> Result<(), LogError>
+    {
+        use std::io::Write;
+
+        // The file is kept open between writes, it is only reopened after a rotation or a path change
+        if self.handle.is_none()
+        {
+            let file = std::fs::OpenOptions::new().create(true).append(true).open(&self.path)
+                .map_err(|source| LogError::Open { path: self.path.clone(), source })?;
+            self.handle = Some(file);
+        }
+
+        let file = self.handle.as_mut().unwrap();
+        // The whole line goes out in a single write so concurrent writers can't tear it
+        let written = file.write_all(line.as_bytes());
+        if let Err(source) = written
+        {
+            // Start over with a fresh handle next time
+            self.handle = None;
+            return Err(LogError::Write { path: self.path.clone(), source });
+        }
+        Ok(())
+    }
+
+    /// Flushes anything still pending out to the log file, and waits for an archive that is still being gzipped
+    pub fn flush(&mut self)
+    {
+        use std::io::Write;
+        if let Some(file) = self.handle.as_mut()
+        {
+            let _ = file.flush();
+        }
+        self.finish_compressing();
+    }
+
+    /// Writes out the message at the "Trace" level
+    pub fn trace(&mut self, message: &str)
+    {
+        self.write_record(&Record::new(Level::Trace, message));
+    }
+
+    /// Writes out the message at the "Debug" level
+    pub fn debug(&mut self, message: &str)
+    {
+        self.write_record(&Record::new(Level::Debug, message));
+    }
+
+    /// Writes out the message at the "Info" level
+    pub fn info(&mut self, message: &str)
+    {
+        self.write_record(&Record::new(Level::Info, message));
+    }
+
+    /// Writes out the message at the "Warn" level
+    pub fn warn(&mut self, message: &str)
+    {
+        self.write_record(&Record::new(Level::Warn, message));
+    }
+
+    /// Writes out the message at the "Error" level
+    pub fn error(&mut self, message: &str)
+    {
+        self.write_record(&Record::new(Level::Error, message));
     }
 
     /// Removes the log file, using the write log again will bring it back. 
@@ -83,7 +239,18 @@ impl LogFile
     /// ```
     pub fn clear_log(&mut self)
     {
-        let _ = std::fs::remove_file(&self.path);
+        let _ = self.try_clear_log();
+    }
+
+    /// Removes the log file, returning why it failed. A log file that doesn't exist yet is not an error.
+    pub fn try_clear_log(&mut self) -> Result<(), LogError>
+    {
+        self.handle = None;
+        match std::fs::remove_file(&self.path)
+        {
+            Err(source) if source.kind() != std::io::ErrorKind::NotFound => Err(LogError::Remove { path: self.path.clone(), source }),
+            _ => Ok(()),
+        }
     }
 
 
@@ -95,10 +262,9 @@ impl LogFile
     /// test.clear_log_larger_than_size(1000);
     /// ```
     /// This would only delete the log file if it is larger than 1kb
-    #[cfg(target_os="windows")]
     pub fn clear_log_larger_than_size(&mut self, size: u64) -> std::io::Result<()>
     {
-        let filesize = std::fs::metadata(&self.path)?.file_size();
+        let filesize = std::fs::metadata(&self.path)?.len();
         if filesize > size
         {
             self.clear_log();
@@ -110,8 +276,8 @@ impl LogFile
     //Updates the information needed so that writing out the logs is up to date
     fn update_path(&mut self) -> &mut Self
     {
-        //println!("{}", format!("{}{}", self.dir, self.name));
-        self.path = std::ffi::OsString::from(format!("{}{}", self.dir, self.name));
+        self.path = self.dir.join(&self.name);
+        self.handle = None;
         self
     }
 }
\ No newline at end of file
@@ -1,23 +1,75 @@
+#[allow(clippy::module_inception)]
 mod log;
+mod console;
+mod error;
+mod facade;
+mod fallback;
+mod format;
+mod level;
+mod reader;
+mod record;
+mod rotate;
+mod shared;
+
+pub use console::Console;
+pub use error::LogError;
+pub use fallback::Fallback;
+pub use format::Format;
+pub use level::Level;
+pub use reader::{Follow, LogReader};
+pub use record::Record;
+pub use shared::SharedLog;
 
 /// Used to create and update actions done by the program using a log file.
 pub struct LogFile {
-    path: std::ffi::OsString,
-    name: String,
-    dir: String,    
+    path: std::path::PathBuf,
+    name: std::path::PathBuf,
+    dir: std::path::PathBuf,
+    level: Level,
+    format: Format,
+    console: Console,
+    color: bool,
+    max_size: Option<u64>,
+    rotate_daily: bool,
+    max_archives: usize,
+    compress_archives: bool,
+    handle: Option<std::fs::File>,
+    fallback: Fallback,
+    buffered: Vec<String>,
+    compressing: Option<(std::path::PathBuf, std::thread::JoinHandle<std::io::Result<()>>)>,
 }
 
 impl Default for LogFile
 {
     fn default() -> Self {
         Self {
-            path: std::ffi::OsString::from(format!("output.log")),
-            name: "output.log".to_string(),
-            dir: String::new(),
+            path: std::path::PathBuf::from("output.log"),
+            name: std::path::PathBuf::from("output.log"),
+            dir: std::path::PathBuf::new(),
+            level: Level::Info,
+            format: Format::Plain,
+            console: Console::Stdout,
+            color: false,
+            max_size: None,
+            rotate_daily: false,
+            max_archives: 5,
+            compress_archives: false,
+            handle: None,
+            fallback: Fallback::Stderr,
+            buffered: Vec::new(),
+            compressing: None,
         }
     }
 }
 
+impl Drop for LogFile
+{
+    fn drop(&mut self) {
+        // Don't leave an archive half gzipped
+        self.finish_compressing();
+    }
+}
+
 impl LogFile {
 
     /// Creates a file "output.log" in the working directory that can be written out to.
@@ -26,9 +78,327 @@ impl LogFile {
     /// let mut test = davids_awesome_library::log::LogFile::new();
     /// test.write_log("success");
     /// ```
-    
     pub fn new() -> Self
     {
         Self::default()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use super::rotate::compressed_path;
+
+    fn temp_log(name: &str) -> LogFile
+    {
+        let mut log = LogFile::new();
+        log.custom_file_path(std::env::temp_dir()).file_name(name);
+        log.clear_log();
+        log
+    }
+
+    #[test]
+    fn level_filter_and_fields() {
+
+        let mut log = temp_log("davids_awesome_library_levels.log");
+        log.set_level(Level::Warn);
+        log.debug("routine chatter");
+        log.write_record(Record::new(Level::Error, "disk failure").field("drive", "C:").field("free", "0"));
+
+        // The default layout is the one from before levels, the level column is opt in
+        log.set_format(Format::PlainWithLevel);
+        log.write_record(&Record::new(Level::Warn, "disk almost full"));
+
+        let contents = std::fs::read_to_string(&log.path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert!(!contents.contains("routine chatter"));
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].split(" | ").skip(1).collect::<Vec<_>>(), vec!["Message: disk failure", "drive=C:", "free=0"]);
+        assert!(lines[1].ends_with(" | WARN | Message: disk almost full"));
+        assert_eq!(log.reader().records().unwrap()[1].level(), Level::Warn);
+        log.clear_log();
+    }
+
+    #[test]
+    fn rotate_by_size() {
+
+        let mut log = temp_log("davids_awesome_library_rotate.log");
+        log.set_max_size(100).set_max_archives(2);
+        for archive in 1..=3
+        {
+            let _ = std::fs::remove_file(log.archive_path(archive));
+        }
+
+        for count in 0..10
+        {
+            log.write_log(&format!("line number {}", count));
+        }
+
+        assert!(std::fs::metadata(&log.path).unwrap().len() <= 100);
+        assert!(log.archive_path(1).exists());
+        assert!(log.archive_path(2).exists());
+        assert!(!log.archive_path(3).exists());
+        assert!(std::fs::read_to_string(&log.path).unwrap().contains("line number 9"));
+
+        // Rotating a log that doesn't exist (yet) is nothing to do, and leaves the archives where they are
+        log.clear_log();
+        log.rotate().unwrap();
+        assert!(log.archive_path(2).exists());
+        assert!(!log.path.exists());
+
+        log.clear_log();
+        for archive in 1..=2
+        {
+            let _ = std::fs::remove_file(log.archive_path(archive));
+        }
+    }
+
+    #[test]
+    fn global_logger() {
+
+        let mut log = temp_log("davids_awesome_library_global.log");
+        log.set_level(Level::Debug);
+        let path = log.path.clone();
+        let shared = log.into_background(16);
+        shared.init_global().unwrap();
+        drop(shared);
+
+        // Only the global logger has the handle now, flushing it still writes out the queue
+        ::log::debug!("sent through the facade");
+        ::log::trace!("filtered out");
+        ::log::logger().flush();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("| Message: sent through the facade | target="));
+        assert!(!contents.contains("filtered out"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn shared_between_threads() {
+
+        let log = temp_log("davids_awesome_library_shared.log");
+        let path = log.path.clone();
+        let shared = log.into_background(16);
+
+        let workers: Vec<_> = (0..8).map(|worker| {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                for count in 0..200
+                {
+                    shared.write_record(Record::new(Level::Info, "from a worker").field("worker", &worker.to_string()).field("count", &count.to_string()));
+                }
+            })
+        }).collect();
+
+        for worker in workers
+        {
+            worker.join().unwrap();
+        }
+        drop(shared);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 8 * 200);
+        assert!(contents.lines().all(|line| line.contains("| Message: from a worker | worker=")));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn json_lines_format() {
+
+        let mut log = temp_log("davids_awesome_library_json.log");
+        log.set_format(Format::JsonLines);
+        log.write_record(Record::new(Level::Warn, "disk \"C\" almost full").field("free", "2GB"));
+
+        let contents = std::fs::read_to_string(&log.path).unwrap();
+        let json: serde_json::Value = serde_json::from_str(contents.lines().next().unwrap()).unwrap();
+        assert_eq!(json["level"], "WARN");
+        assert_eq!(json["message"], "disk \"C\" almost full");
+        assert_eq!(json["fields"]["free"], "2GB");
+        assert!(chrono::DateTime::parse_from_rfc3339(json["timestamp"].as_str().unwrap()).is_ok());
+        log.clear_log();
+    }
+
+    #[test]
+    fn template_format() {
+
+        let mut record = Record::new(Level::Error, "install {level} failed");
+        record.field("code", "1603");
+
+        let format = Format::Template("{level}: {message} ({field:code}) {unknown}".to_string());
+        assert_eq!(format.render(&record), "ERROR: install {level} failed (1603) {unknown}");
+    }
+
+    #[test]
+    fn read_records_back() {
+
+        let mut log = temp_log("davids_awesome_library_reader.log");
+        log.set_console(Console::Off).set_level(Level::Trace).set_max_archives(2);
+        log.set_format(Format::PlainWithLevel);
+        for archive in 1..=2
+        {
+            let _ = std::fs::remove_file(log.archive_path(archive));
+        }
+
+        log.debug("before the rotation");
+        log.write_record(Record::new(Level::Error, "install failed | retrying").field("code", "1603"));
+        log.rotate().unwrap();
+        log.warn("after the rotation");
+        log.write_record(Record::new(Level::Info, "spread over\nmore | lines\n").field("lines", "3"));
+        log.info("routine");
+
+        let records = LogReader::new(log.path()).records().unwrap();
+        let messages: Vec<&str> = records.iter().map(|record| record.message()).collect();
+        assert_eq!(messages, vec!["before the rotation", "install failed | retrying", "after the rotation", "spread over\nmore | lines\n", "routine"]);
+        assert_eq!(records[1].fields(), &[("code".to_string(), "1603".to_string())]);
+        assert_eq!(records[3].fields(), &[("lines".to_string(), "3".to_string())]);
+
+        let warnings = log.reader().min_level(Level::Warn).records().unwrap();
+        assert_eq!(warnings.len(), 2);
+
+        // The documented limit of the plain layout, a message ending in " | key=value" comes back as a field
+        let ambiguous = Record::parse(&Record::new(Level::Info, "retrying | attempt=2").to_line(false)).unwrap();
+        assert_eq!(ambiguous.message(), "retrying");
+        assert_eq!(ambiguous.fields(), &[("attempt".to_string(), "2".to_string())]);
+
+        let matched = log.reader().include_archives(false).matching("^after").unwrap().records().unwrap();
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].level(), Level::Warn);
+
+        log.clear_log();
+        for archive in 1..=2
+        {
+            let _ = std::fs::remove_file(log.archive_path(archive));
+        }
+    }
+
+    #[test]
+    fn follow_new_records() {
+
+        let mut log = temp_log("davids_awesome_library_follow.log");
+        log.set_console(Console::Off);
+        log.write_log("already there");
+
+        let mut follow = log.reader().poll_interval(std::time::Duration::from_millis(10)).contains("new").follow().unwrap();
+        let path = log.path.clone();
+        let writer = std::thread::spawn(move || {
+            log.write_log("skipped");
+            log.write_log("new line\nand the rest of it");
+            log.rotate().unwrap();
+            log.write_log("new file");
+            log
+        });
+
+        assert_eq!(follow.next().unwrap().message(), "new line\nand the rest of it");
+        assert_eq!(follow.next().unwrap().message(), "new file");
+
+        let mut log = writer.join().unwrap();
+        log.clear_log();
+        let _ = std::fs::remove_file(log.archive_path(1));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn unavailable_log_file() {
+
+        let mut log = LogFile::new();
+        log.custom_file_path(std::env::temp_dir().join("davids_awesome_library_missing_dir")).set_console(Console::Off);
+
+        let error = log.try_write_log("lost").unwrap_err();
+        assert!(matches!(error, LogError::Open { .. }));
+        assert!(error.path().ends_with("output.log"));
+        assert_eq!(std::io::Error::from(error).kind(), std::io::ErrorKind::NotFound);
+
+        log.set_fallback(Fallback::Memory);
+        log.warn("kept");
+        let buffered = log.take_buffered();
+        assert_eq!(buffered.len(), 1);
+        assert!(buffered[0].ends_with("| Message: kept"));
+
+        let alternate = std::env::temp_dir().join("davids_awesome_library_fallback.log");
+        let _ = std::fs::remove_file(&alternate);
+        log.set_fallback(Fallback::Path(alternate.clone()));
+        log.error("redirected");
+        assert!(std::fs::read_to_string(&alternate).unwrap().contains("| Message: redirected"));
+        let _ = std::fs::remove_file(&alternate);
+    }
+
+    #[test]
+    fn failed_rotation() {
+
+        let mut log = temp_log("davids_awesome_library_rotate_failure.log");
+        log.set_console(Console::Off).set_max_size(10).set_max_archives(1).set_fallback(Fallback::Memory);
+
+        // A folder in the way of the oldest archive can't be removed to make room
+        let blocked = log.archive_path(1);
+        std::fs::create_dir_all(&blocked).unwrap();
+
+        log.write_log("first");
+        log.write_log("second");
+
+        let buffered = log.take_buffered();
+        assert_eq!(buffered.len(), 1);
+        assert!(buffered[0].contains("Message: Could Not Rotate Log File"));
+        assert!(std::fs::read_to_string(&log.path).unwrap().contains("Message: second"));
+
+        log.clear_log();
+        let _ = std::fs::remove_dir(&blocked);
+    }
+
+    #[test]
+    fn compressed_archives() {
+
+        let mut log = temp_log("davids_awesome_library_gzip.log");
+        log.set_console(Console::Off).set_max_archives(2).set_compress_archives(true);
+
+        log.write_log("first");
+        log.rotate().unwrap();
+        log.write_log("second");
+        log.rotate().unwrap();
+        log.write_log("third");
+        log.flush();
+
+        let first = compressed_path(&log.archive_path(2));
+        assert!(first.exists());
+        assert!(!log.archive_path(1).exists());
+
+        let messages: Vec<String> = log.reader().records().unwrap().iter().map(|record| record.message().to_string()).collect();
+        assert_eq!(messages, vec!["first", "second", "third"]);
+
+        let mut file = crate::files::File::new();
+        assert!(file.set_file(first.to_str().unwrap()).last_file_line().unwrap().ends_with("Message: first"));
+
+        log.clear_log();
+        for archive in 1..=2
+        {
+            let _ = std::fs::remove_file(compressed_path(&log.archive_path(archive)));
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn failed_compression() {
+
+        // "<name>.1.log" just fits in the 255 bytes allowed for a file name, with ".gz" on the end it doesn't
+        let name = format!("{}.log", "x".repeat(255 - ".1.log".len()));
+        let mut log = temp_log(&name);
+        log.set_console(Console::Off).set_max_archives(1).set_compress_archives(true).set_fallback(Fallback::Memory);
+        let _ = std::fs::remove_file(log.archive_path(1));
+
+        log.write_log("first");
+        log.try_write_log("kept").unwrap();
+        log.rotate().unwrap();
+        log.write_log("second");
+        log.flush();
+
+        let buffered = log.take_buffered();
+        assert_eq!(buffered.len(), 1);
+        assert!(buffered[0].contains("Message: Could Not Compress Log Archive"));
+        assert!(std::fs::read_to_string(log.archive_path(1)).unwrap().contains("Message: kept"));
+        assert!(std::fs::read_to_string(&log.path).unwrap().contains("Message: second"));
+
+        log.clear_log();
+        let _ = std::fs::remove_file(log.archive_path(1));
+    }
+}
@@ -0,0 +1,285 @@
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+
+use crate::files::FollowedFile;
+use crate::log::rotate::find_archive;
+use crate::log::{Level, LogFile, Record};
+
+/// Reads the records written out by a LogFile back, optionally filtered.
+/// Works with the plain text and JSON Lines formats, lines it cannot read are skipped.
+/// A plain text message that spans several lines is read back whole, the lines that don't start a record are its continuation.
+/// # Examples
+/// ```
+/// use davids_awesome_library::log::{Level, LogFile};
+///
+/// let mut test = LogFile::new();
+/// test.write_log("success");
+///
+/// let records = test.reader().min_level(Level::Info).contains("success").records().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct LogReader
+{
+    path: PathBuf,
+    include_archives: bool,
+    since: Option<chrono::DateTime<chrono::Local>>,
+    until: Option<chrono::DateTime<chrono::Local>>,
+    level: Option<Level>,
+    contains: Option<String>,
+    regex: Option<regex::Regex>,
+    poll_interval: std::time::Duration,
+}
+
+impl LogFile
+{
+    /// A reader for the records in this log file and its rotated archives
+    pub fn reader(&self) -> LogReader
+    {
+        LogReader::new(&self.path)
+    }
+}
+
+impl LogReader
+{
+    /// A reader for the log file at the path, rotated archives next to it ("output.1.log", "output.2.log.gz", ...) are read as well
+    pub fn new<P: AsRef<Path>>(path: P) -> Self
+    {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            include_archives: true,
+            since: None,
+            until: None,
+            level: None,
+            contains: None,
+            regex: None,
+            poll_interval: std::time::Duration::from_millis(250),
+        }
+    }
+
+    /// Whether the rotated archives are read along with the current log file, true by default
+    pub fn include_archives(&mut self, enabled: bool) -> &mut Self
+    {
+        self.include_archives = enabled;
+        self
+    }
+
+    /// Only keep records written at or after this time
+    pub fn since(&mut self, time: chrono::DateTime<chrono::Local>) -> &mut Self
+    {
+        self.since = Some(time);
+        self
+    }
+
+    /// Only keep records written at or before this time
+    pub fn until(&mut self, time: chrono::DateTime<chrono::Local>) -> &mut Self
+    {
+        self.until = Some(time);
+        self
+    }
+
+    /// Only keep records at or above this level.
+    /// Lines written with Format::Plain have no level and are read as Info, so they are all dropped by a minimum above Info.
+    /// Use Format::PlainWithLevel or Format::JsonLines to filter on it.
+    pub fn min_level(&mut self, level: Level) -> &mut Self
+    {
+        self.level = Some(level);
+        self
+    }
+
+    /// Only keep records whose message contains the text
+    pub fn contains(&mut self, text: &str) -> &mut Self
+    {
+        self.contains = Some(text.to_string());
+        self
+    }
+
+    /// Only keep records whose message matches the regex pattern
+    pub fn matching(&mut self, pattern: &str) -> io::Result<&mut Self>
+    {
+        let regex = regex::Regex::new(pattern);
+        if regex.is_err()
+        {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Regex Pattern Invalid"));
+        }
+        self.regex = regex.ok();
+        Ok(self)
+    }
+
+    /// How long "follow" waits before checking the log file for new lines again, 250ms by default
+    pub fn poll_interval(&mut self, interval: std::time::Duration) -> &mut Self
+    {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Reads every matching record, oldest first (the oldest archive through to the current log file)
+    pub fn records(&self) -> io::Result<Vec<Record>>
+    {
+        let mut records = Vec::new();
+        for path in self.files()
+        {
+            let file = std::fs::File::open(&path)?;
+            let file: Box<dyn io::Read> = match path.extension()
+            {
+                Some(extension) if extension == "gz" => Box::new(flate2::read::MultiGzDecoder::new(file)),
+                _ => Box::new(file),
+            };
+
+            let mut pending = Pending::default();
+            for line in io::BufReader::new(file).split(b'\n')
+            {
+                let finished = pending.push(&String::from_utf8_lossy(&line?));
+                records.extend(finished.filter(|record| self.is_match(record)));
+            }
+            records.extend(pending.finish().filter(|record| self.is_match(record)));
+        }
+        Ok(records)
+    }
+
+    /// Waits for records to be appended to the log file and yields the matching ones as they come in,
+    /// like "tail -f". Records already in the file are skipped. Keeps following the file across rotations.
+    /// # Examples
+    /// ```no_run
+    /// let test = davids_awesome_library::log::LogFile::new();
+    /// for record in test.reader().follow().unwrap()
+    /// {
+    ///     println!("{}", record);
+    /// }
+    /// ```
+    pub fn follow(&self) -> io::Result<Follow>
+    {
+        Ok(Follow {
+            reader: self.clone(),
+            followed: FollowedFile::new(&self.path)?,
+            pending: Pending::default(),
+        })
+    }
+
+    /// The files to read in order, the highest numbered (oldest) archive first
+    fn files(&self) -> Vec<PathBuf>
+    {
+        let mut files = Vec::new();
+        if self.include_archives
+        {
+            let mut index = 1;
+            while let Some(archive) = find_archive(&self.path, index)
+            {
+                files.push(archive);
+                index += 1;
+            }
+            files.reverse();
+        }
+
+        if self.path.exists()
+        {
+            files.push(self.path.clone());
+        }
+        files
+    }
+
+    fn is_match(&self, record: &Record) -> bool
+    {
+        if self.since.is_some_and(|since| record.timestamp() < since)
+        {
+            return false;
+        }
+        if self.until.is_some_and(|until| record.timestamp() > until)
+        {
+            return false;
+        }
+        if self.level.is_some_and(|level| record.level() < level)
+        {
+            return false;
+        }
+        if let Some(text) = &self.contains
+        {
+            if !record.message().contains(text.as_str())
+            {
+                return false;
+            }
+        }
+        if let Some(regex) = &self.regex
+        {
+            if !regex.is_match(record.message())
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Yields the records appended to a log file, created by LogReader::follow
+pub struct Follow
+{
+    reader: LogReader,
+    followed: FollowedFile,
+    pending: Pending,
+}
+
+/// Puts a record back together from its lines, a message with line breaks is written out over several lines
+#[derive(Debug, Default)]
+struct Pending
+{
+    text: Option<String>,
+}
+
+impl Pending
+{
+    /// Adds the next line, handing back the record before it once the line turns out to start a new one.
+    /// A line that doesn't start a record belongs to the message of the one before, or is skipped if there is none.
+    fn push(&mut self, line: &str) -> Option<Record>
+    {
+        let line = line.trim_end_matches('\r');
+        if Record::parse(line).is_some()
+        {
+            let finished = self.finish();
+            self.text = Some(line.to_string());
+            return finished;
+        }
+
+        if let Some(text) = self.text.as_mut()
+        {
+            text.push('\n');
+            text.push_str(line);
+        }
+        None
+    }
+
+    /// The record still being put together
+    fn finish(&mut self) -> Option<Record>
+    {
+        Record::parse(&self.text.take()?)
+    }
+}
+
+impl Iterator for Follow
+{
+    type Item = Record;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop
+        {
+            let replaced = self.followed.is_replaced();
+
+            while let Ok(Some(line)) = self.followed.next_line()
+            {
+                if let Some(record) = self.pending.push(&line).filter(|record| self.reader.is_match(record))
+                {
+                    return Some(record);
+                }
+            }
+            // A record is written out in one go, so once there's nothing more to read the last one is complete
+            if let Some(record) = self.pending.finish().filter(|record| self.reader.is_match(record))
+            {
+                return Some(record);
+            }
+
+            if replaced && self.followed.reopen().is_ok()
+            {
+                continue;
+            }
+            std::thread::sleep(self.reader.poll_interval);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use super::Level;
+
+/// The timestamp layout used when writing records out as plain text.
+pub(crate) const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A single entry for the log file: a level, a message and optional key/value fields.
+/// # Examples
+/// ```
+/// use davids_awesome_library::log::{Level, LogFile, Record};
+///
+/// let mut test = LogFile::new();
+/// test.write_record(Record::new(Level::Warn, "disk almost full").field("free", "2GB"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record
+{
+    timestamp: chrono::DateTime<chrono::Local>,
+    level: Level,
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl Record
+{
+    /// Creates a record stamped with the current local time.
+    pub fn new(level: Level, message: &str) -> Self
+    {
+        Self {
+            timestamp: chrono::offset::Local::now(),
+            level,
+            message: message.to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Attaches a key/value field to the record, fields are written out in the order they were added.
+    pub fn field(&mut self, key: &str, value: &str) -> &mut Self
+    {
+        self.fields.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Reads a record back from a line written out with the plain text or JSON Lines format.
+    /// Lines written before levels existed ("<timestamp> | Message: <text>") are read as "Info".
+    /// Trailing " | key=value" parts of a plain line are always read as fields, even if they were written as part of the message.
+    /// Returns None if the line is not a record.
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::log::{Level, Record};
+    ///
+    /// let record = Record::parse("2024-11-14 09:30:00 | WARN | Message: disk almost full | free=2GB").unwrap();
+    /// assert_eq!(record.level(), Level::Warn);
+    /// assert_eq!(record.message(), "disk almost full");
+    /// ```
+    pub fn parse(line: &str) -> Option<Self>
+    {
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.starts_with('{')
+        {
+            return Self::parse_json(line);
+        }
+
+        let (timestamp, rest) = line.split_once(" | ")?;
+        let timestamp = chrono::NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+        let timestamp = timestamp.and_local_timezone(chrono::Local).earliest()?;
+
+        let (level, rest) = match rest.strip_prefix("Message: ")
+        {
+            Some(rest) => (Level::Info, rest),
+            None => {
+                let (level, rest) = rest.split_once(" | ")?;
+                (level.parse().ok()?, rest.strip_prefix("Message: ")?)
+            }
+        };
+
+        // Trailing " | key=value" parts are fields, anything else after a " | " belongs to the message
+        let mut parts: Vec<&str> = rest.split(" | ").collect();
+        let mut fields = Vec::new();
+        while parts.len() > 1
+        {
+            let field = parts.last().and_then(|part| part.split_once('='));
+            match field
+            {
+                Some((key, value)) if !key.is_empty() && !key.contains(' ') => {
+                    fields.push((key.to_string(), value.to_string()));
+                    parts.pop();
+                }
+                _ => break,
+            }
+        }
+        fields.reverse();
+
+        Some(Self {
+            timestamp,
+            level,
+            message: parts.join(" | "),
+            fields,
+        })
+    }
+
+    fn parse_json(line: &str) -> Option<Self>
+    {
+        let json: serde_json::Value = serde_json::from_str(line).ok()?;
+        let timestamp = chrono::DateTime::parse_from_rfc3339(json["timestamp"].as_str()?).ok()?;
+        let mut fields = Vec::new();
+        if let Some(object) = json["fields"].as_object()
+        {
+            for (key, value) in object
+            {
+                let value = match value.as_str()
+                {
+                    Some(text) => text.to_string(),
+                    None => value.to_string(),
+                };
+                fields.push((key.clone(), value));
+            }
+        }
+
+        Some(Self {
+            timestamp: timestamp.with_timezone(&chrono::Local),
+            level: json["level"].as_str()?.parse().ok()?,
+            message: json["message"].as_str()?.to_string(),
+            fields,
+        })
+    }
+
+    pub fn timestamp(&self) -> chrono::DateTime<chrono::Local>
+    {
+        self.timestamp
+    }
+
+    pub fn level(&self) -> Level
+    {
+        self.level
+    }
+
+    pub fn message(&self) -> &str
+    {
+        &self.message
+    }
+
+    pub fn fields(&self) -> &[(String, String)]
+    {
+        &self.fields
+    }
+
+    /// The plain text line, with or without the level column
+    pub(crate) fn to_line(&self, with_level: bool) -> String
+    {
+        let mut line = self.timestamp.format(TIMESTAMP_FORMAT).to_string();
+        if with_level
+        {
+            line.push_str(" | ");
+            line.push_str(self.level.as_str());
+        }
+        line.push_str(" | Message: ");
+        line.push_str(&self.message);
+        for (key, value) in &self.fields
+        {
+            line.push_str(&format!(" | {}={}", key, value));
+        }
+        line
+    }
+}
+
+/// Renders the record as the plain text line used by the log file (without the trailing newline), the same layout as before
+/// records had levels, eg. "2024-11-14 09:30:00 | Message: disk almost full | free=2GB"
+impl std::fmt::Display for Record
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_line(false))
+    }
+}
@@ -0,0 +1,266 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::log::{LogError, LogFile};
+
+impl LogFile
+{
+    /// Rolls the log file over once it would grow past the size in bytes.
+    /// "output.log" becomes "output.1.log", the previous "output.1.log" becomes "output.2.log" and so on.
+    /// # Examples
+    /// ```
+    /// use davids_awesome_library::files::size::MEGABYTE;
+    ///
+    /// let mut test = davids_awesome_library::log::LogFile::new();
+    /// test.custom_file_path(std::env::temp_dir());
+    /// test.set_max_size(10 * MEGABYTE).set_max_archives(3);
+    /// test.write_log("success");
+    /// ```
+    pub fn set_max_size(&mut self, size: u64) -> &mut Self
+    {
+        self.max_size = Some(size);
+        self
+    }
+
+    /// Rolls the log file over on the first write of a new day
+    /// # Examples
+    /// ```
+    /// let mut test = davids_awesome_library::log::LogFile::new();
+    /// test.custom_file_path(std::env::temp_dir());
+    /// test.set_daily_rotation(true);
+    /// test.write_log("success");
+    /// ```
+    pub fn set_daily_rotation(&mut self, enabled: bool) -> &mut Self
+    {
+        self.rotate_daily = enabled;
+        self
+    }
+
+    /// The amount of rolled over log files to keep, the oldest is deleted once there are more. Defaults to 5.
+    pub fn set_max_archives(&mut self, count: usize) -> &mut Self
+    {
+        self.max_archives = count;
+        self
+    }
+
+    /// Rolls the log file over right away, regardless of its size or age. A log file that doesn't exist yet is left as it is.
+    /// # Examples
+    /// ```
+    /// let mut test = davids_awesome_library::log::LogFile::new();
+    /// test.custom_file_path(std::env::temp_dir());
+    /// test.write_log("success");
+    /// let _ = test.rotate();
+    /// ```
+    pub fn rotate(&mut self) -> io::Result<()>
+    {
+        // Windows won't rename a file that is still open
+        self.handle = None;
+        // The archive from the last rotation has to be done before the archives are shifted
+        self.finish_compressing();
+
+        // Nothing written yet means nothing to roll over, and the archives shouldn't be shifted for it
+        if !self.path.exists()
+        {
+            return Ok(());
+        }
+
+        if self.max_archives == 0
+        {
+            return ignore_missing(std::fs::remove_file(&self.path));
+        }
+
+        // Drop the oldest archive so there is room to shift everything else up by one
+        for oldest in [self.archive_path(self.max_archives), compressed_path(&self.archive_path(self.max_archives))]
+        {
+            if oldest.exists()
+            {
+                std::fs::remove_file(&oldest)?;
+            }
+        }
+
+        for index in (1..self.max_archives).rev()
+        {
+            let from = self.archive_path(index);
+            if from.exists()
+            {
+                std::fs::rename(&from, self.archive_path(index + 1))?;
+            }
+
+            let from = compressed_path(&from);
+            if from.exists()
+            {
+                std::fs::rename(&from, compressed_path(&self.archive_path(index + 1)))?;
+            }
+        }
+
+        // Removed from under us since it was checked
+        ignore_missing(std::fs::rename(&self.path, self.archive_path(1)))?;
+        if self.compress_archives && self.archive_path(1).exists()
+        {
+            self.start_compressing(self.archive_path(1));
+        }
+        Ok(())
+    }
+
+    /// Gzips the archive on a background thread so the write that caused the rotation doesn't wait for it
+    fn start_compressing(&mut self, archive: PathBuf)
+    {
+        let thread_archive = archive.clone();
+        let thread = std::thread::Builder::new()
+            .name("log-compress".to_string())
+            .spawn(move || compress(&thread_archive));
+
+        match thread
+        {
+            Ok(thread) => self.compressing = Some((archive, thread)),
+            // No thread to hand it to, so it is done right here instead
+            Err(_) => {
+                if let Err(source) = compress(&archive)
+                {
+                    self.write_fallback_error(LogError::Compress { path: archive, source });
+                }
+            }
+        }
+    }
+
+    /// Waits for the archive that is being gzipped, a failure is sent to the fallback and leaves the archive uncompressed
+    pub(crate) fn finish_compressing(&mut self)
+    {
+        if let Some((archive, thread)) = self.compressing.take()
+        {
+            let result = thread.join().unwrap_or_else(|_| Err(io::Error::other("Compression Panicked")));
+            if let Err(source) = result
+            {
+                self.write_fallback_error(LogError::Compress { path: archive, source });
+            }
+        }
+    }
+
+    /// Gzips the archives as they are rolled over, "output.log" becomes "output.1.log.gz".
+    /// The gzipping is done on a background thread, "flush" waits for it. If it fails the archive is kept uncompressed
+    /// and the error is sent to the fallback, see "set_fallback".
+    /// They can still be read with "LogReader" and the "files::File" reading helpers.
+    /// # Examples
+    /// ```
+    /// let mut test = davids_awesome_library::log::LogFile::new();
+    /// test.set_max_size(davids_awesome_library::files::size::MEGABYTE).set_compress_archives(true);
+    /// test.write_log("success");
+    /// ```
+    pub fn set_compress_archives(&mut self, enabled: bool) -> &mut Self
+    {
+        self.compress_archives = enabled;
+        self
+    }
+
+    /// The path of the archive at the index, "output.log" with an index of 2 is "output.2.log"
+    pub(crate) fn archive_path(&self, index: usize) -> PathBuf
+    {
+        archive_path(&self.path, index)
+    }
+
+    /// Checks the current log file against the size and daily limits before writing "incoming" bytes
+    pub(crate) fn rotate_if_needed(&mut self, incoming: u64) -> io::Result<()>
+    {
+        if self.max_size.is_none() && !self.rotate_daily
+        {
+            return Ok(());
+        }
+
+        let metadata = std::fs::metadata(&self.path);
+        if metadata.is_err()
+        {
+            // The file was removed from under us, open a fresh one on the next write
+            self.handle = None;
+            return Ok(());
+        }
+        let metadata = metadata.unwrap();
+
+        let mut rotate = false;
+
+        if let Some(max_size) = self.max_size
+        {
+            rotate = metadata.len() > 0 && metadata.len() + incoming > max_size;
+        }
+
+        if self.rotate_daily && !rotate
+        {
+            if let Ok(modified) = metadata.modified()
+            {
+                let last_write = chrono::DateTime::<chrono::Local>::from(modified).date_naive();
+                rotate = last_write < chrono::offset::Local::now().date_naive();
+            }
+        }
+
+        if rotate
+        {
+            return self.rotate();
+        }
+        Ok(())
+    }
+}
+
+/// The path of the archive at the index for a log file, "output.log" with an index of 2 is "output.2.log"
+pub(crate) fn archive_path(path: &Path, index: usize) -> PathBuf
+{
+    // Built up as an OsString so non UTF-8 file names survive
+    let mut name = path.file_stem().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", index));
+    if let Some(extension) = path.extension()
+    {
+        name.push(".");
+        name.push(extension);
+    }
+    path.with_file_name(name)
+}
+
+/// The gzipped version of a path, "output.1.log" becomes "output.1.log.gz"
+pub(crate) fn compressed_path(path: &Path) -> PathBuf
+{
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".gz");
+    PathBuf::from(name)
+}
+
+/// The archive at the index for a log file, whether or not it was compressed
+pub(crate) fn find_archive(path: &Path, index: usize) -> Option<PathBuf>
+{
+    let plain = archive_path(path, index);
+    if plain.exists()
+    {
+        return Some(plain);
+    }
+
+    let compressed = compressed_path(&plain);
+    if compressed.exists()
+    {
+        return Some(compressed);
+    }
+    None
+}
+
+/// A file that is already gone counts as removed (or renamed)
+fn ignore_missing(result: io::Result<()>) -> io::Result<()>
+{
+    match result
+    {
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// Replaces the file with a gzipped copy of it, the file is left as it is if that fails
+fn compress(path: &Path) -> io::Result<()>
+{
+    let compressed = compressed_path(path);
+    let mut source = std::fs::File::open(path)?;
+    let mut encoder = flate2::write::GzEncoder::new(std::fs::File::create(&compressed)?, flate2::Compression::default());
+    let written = io::copy(&mut source, &mut encoder).and_then(|_| encoder.finish());
+    if let Err(error) = written
+    {
+        // Don't leave a half written copy next to the archive
+        let _ = std::fs::remove_file(&compressed);
+        return Err(error);
+    }
+    drop(source);
+    std::fs::remove_file(path)
+}
@@ -0,0 +1,227 @@
+use std::sync::mpsc::{Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use crate::log::{Level, LogFile, Record};
+
+/// A cloneable handle to a LogFile that can be sent to and written from any thread.
+/// Every line is written out whole, so lines from different threads never interleave.
+/// # Examples
+/// ```
+/// let log = davids_awesome_library::log::LogFile::new().into_shared();
+///
+/// let worker_log = log.clone();
+/// std::thread::spawn(move || worker_log.write_log("from a worker")).join().unwrap();
+/// log.write_log("from the main thread");
+/// ```
+#[derive(Clone)]
+pub struct SharedLog
+{
+    inner: Arc<Inner>,
+}
+
+struct Inner
+{
+    level: Level,
+    writer: Writer,
+}
+
+enum Writer
+{
+    /// Each call locks the LogFile and writes the line out itself
+    Locked(Mutex<LogFile>),
+    /// Lines are queued up and written out by a dedicated thread
+    Background {
+        sender: Option<SyncSender<Message>>,
+        thread: Option<JoinHandle<()>>,
+    },
+}
+
+enum Message
+{
+    Write(Record),
+    Flush(SyncSender<()>),
+}
+
+impl LogFile
+{
+    /// Turns the LogFile into a handle that can be cloned and shared between threads.
+    /// Writes are done by the calling thread while holding a lock.
+    pub fn into_shared(self) -> SharedLog
+    {
+        SharedLog {
+            inner: Arc::new(Inner {
+                level: self.level,
+                writer: Writer::Locked(Mutex::new(self)),
+            }),
+        }
+    }
+
+    /// Turns the LogFile into a handle that can be cloned and shared between threads.
+    /// Writes are queued up (at most "capacity" records) and done by a background thread,
+    /// a full queue makes the caller wait rather than drop the record.
+    /// The queue is drained and the thread stopped once the last handle is dropped, or drained on "flush".
+    /// If the thread can't be started the writes are done by the caller instead, the same as "into_shared".
+    /// # Examples
+    /// ```
+    /// let log = davids_awesome_library::log::LogFile::new().into_background(1024);
+    /// log.write_log("success");
+    /// log.flush();
+    /// ```
+    pub fn into_background(self, capacity: usize) -> SharedLog
+    {
+        let level = self.level;
+        let (sender, receiver) = std::sync::mpsc::sync_channel(capacity);
+        // The LogFile is only handed over once the thread is running, so it isn't lost if the thread can't be started
+        let (hand_over, take_over) = std::sync::mpsc::sync_channel(1);
+        let thread = std::thread::Builder::new()
+            .name("log-writer".to_string())
+            .spawn(move || {
+                if let Ok(file) = take_over.recv()
+                {
+                    Self::background_writer(file, receiver);
+                }
+            });
+
+        let thread = match thread
+        {
+            Ok(thread) => thread,
+            Err(_) => return self.into_shared(),
+        };
+        let _ = hand_over.send(self);
+
+        SharedLog {
+            inner: Arc::new(Inner {
+                level,
+                writer: Writer::Background {
+                    sender: Some(sender),
+                    thread: Some(thread),
+                },
+            }),
+        }
+    }
+
+    fn background_writer(mut file: LogFile, receiver: Receiver<Message>)
+    {
+        for message in receiver
+        {
+            match message
+            {
+                Message::Write(record) => file.write_record(&record),
+                Message::Flush(done) => {
+                    file.flush();
+                    let _ = done.send(());
+                }
+            }
+        }
+        file.flush();
+    }
+}
+
+impl SharedLog
+{
+    /// Returns true if a record of this level would be written out
+    pub fn enabled(&self, level: Level) -> bool
+    {
+        level >= self.inner.level
+    }
+
+    /// The minimum level a record needs for it to be written out
+    pub(crate) fn inner_level(&self) -> Level
+    {
+        self.inner.level
+    }
+
+    /// Writes out the custom message to the log file at the "Info" level
+    pub fn write_log(&self, message: &str)
+    {
+        self.write_record(&Record::new(Level::Info, message));
+    }
+
+    /// Writes out a record to the log file if its level is at or above the minimum level
+    pub fn write_record(&self, record: &Record)
+    {
+        if !self.enabled(record.level())
+        {
+            return;
+        }
+
+        match &self.inner.writer
+        {
+            Writer::Locked(file) => {
+                // A poisoned lock only means another thread panicked mid-write, the file itself is still usable
+                let mut file = file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                file.write_record(record);
+            }
+            Writer::Background { sender: Some(sender), .. } => {
+                let _ = sender.send(Message::Write(record.clone()));
+            }
+            Writer::Background { sender: None, .. } => {}
+        }
+    }
+
+    /// Writes out the message at the "Trace" level
+    pub fn trace(&self, message: &str)
+    {
+        self.write_record(&Record::new(Level::Trace, message));
+    }
+
+    /// Writes out the message at the "Debug" level
+    pub fn debug(&self, message: &str)
+    {
+        self.write_record(&Record::new(Level::Debug, message));
+    }
+
+    /// Writes out the message at the "Info" level
+    pub fn info(&self, message: &str)
+    {
+        self.write_record(&Record::new(Level::Info, message));
+    }
+
+    /// Writes out the message at the "Warn" level
+    pub fn warn(&self, message: &str)
+    {
+        self.write_record(&Record::new(Level::Warn, message));
+    }
+
+    /// Writes out the message at the "Error" level
+    pub fn error(&self, message: &str)
+    {
+        self.write_record(&Record::new(Level::Error, message));
+    }
+
+    /// Waits until every record written so far has reached the log file
+    pub fn flush(&self)
+    {
+        match &self.inner.writer
+        {
+            Writer::Locked(file) => {
+                let mut file = file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                file.flush();
+            }
+            Writer::Background { sender: Some(sender), .. } => {
+                let (done, wait) = std::sync::mpsc::sync_channel(1);
+                if sender.send(Message::Flush(done)).is_ok()
+                {
+                    let _ = wait.recv();
+                }
+            }
+            Writer::Background { sender: None, .. } => {}
+        }
+    }
+}
+
+impl Drop for Inner
+{
+    fn drop(&mut self) {
+        if let Writer::Background { sender, thread } = &mut self.writer
+        {
+            // Closing the channel lets the writer thread finish the queue and exit
+            drop(sender.take());
+            if let Some(thread) = thread.take()
+            {
+                let _ = thread.join();
+            }
+        }
+    }
+}
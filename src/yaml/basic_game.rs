@@ -51,9 +51,9 @@ impl GameData {
         .iter()
         .position(|pos| pos.name == name);
 
-        if index.is_some()
+        if let Some(index) = index
         {
-            self.data.game[index.unwrap()] = game;
+            self.data.game[index] = game;
         } else {
             self.data.game.push(game);
         }
@@ -75,7 +75,7 @@ impl GameData {
 
     pub fn save_data(&mut self)
     {
-        let file = std::fs::File::options().write(true).create(true).open(Self::file_dir()).unwrap();
+        let file = std::fs::File::options().write(true).create(true).truncate(false).open(Self::file_dir()).unwrap();
         let serde_val = serde_yaml::to_value(&self.data).unwrap();
         let _ = serde_yaml::to_writer(file, &serde_val);
     }
@@ -87,7 +87,7 @@ impl GameData {
 
     pub fn get_version(&mut self) -> f64
     {
-        self.data.version.clone()
+        self.data.version
     }
 
     fn load_data() -> Data
@@ -103,7 +103,7 @@ impl GameData {
 
     fn validation_check() -> Result<Data, serde_yaml::Error>
     {
-        let file = std::fs::read_to_string(Self::file_dir()).unwrap_or(String::new());
+        let file = std::fs::read_to_string(Self::file_dir()).unwrap_or_default();
         let game: Data = serde_yaml::from_str::<Data>(&file)?;
         Ok(game)
     }
@@ -117,7 +117,7 @@ impl GameData {
             let home_dir = std::env::current_dir().unwrap().to_str();
         }
 
-        let home_dir = String::from(home_dir.unwrap());
+        let home_dir = home_dir.unwrap();
        
         let _ = std::fs::create_dir(format!("{}/.tabletop", home_dir));
         let file_dir = format!("{}/.tabletop/{}", home_dir, YAML_FILE);
@@ -160,6 +160,13 @@ pub struct Game
     shops: Vec<Shop>,
 }
 
+impl Default for Game
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Game {
     pub fn new() -> Self
     {
@@ -186,11 +193,11 @@ impl Game {
         .iter()
         .position(|pos| pos.name == player.name);
 
-        if temp_pos.is_none()
+        if let Some(temp_pos) = temp_pos
         {
-            self.players.push(player);
+            self.players[temp_pos] = player;
         } else {
-            self.players[temp_pos.unwrap()] = player;
+            self.players.push(player);
         }
         
     }
@@ -234,11 +241,11 @@ impl Game {
         .iter()
         .position(|pos| pos.name == shop.name);
 
-        if temp_pos.is_none()
+        if let Some(temp_pos) = temp_pos
         {
-            self.shops.push(shop);
+            self.shops[temp_pos] = shop;
         } else {
-            self.shops[temp_pos.unwrap()] = shop;
+            self.shops.push(shop);
         }
     }
 
@@ -291,6 +298,13 @@ pub struct Player
     inventory: std::collections::BTreeMap<String, i64>,
 }
 
+impl Default for Player
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Player
 {
     pub fn new() -> Self
@@ -367,6 +381,13 @@ pub struct Shop
 }
 
 
+impl Default for Shop
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Shop
 {
     pub fn new() -> Self